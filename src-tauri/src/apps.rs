@@ -0,0 +1,231 @@
+//! Installed application discovery.
+//!
+//! Walks the standard app folders for `.app` bundles and reads each bundle's
+//! `Contents/Info.plist` (XML or binary). Only std and the plist crate are
+//! used, so bundles can be read from any directory on any OS.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// How deep to look for bundles below each root, e.g.
+/// `/Applications/Utilities/Foo.app` is depth 2.
+const MAX_DEPTH: usize = 4;
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct InstalledApp {
+    pub name: String,
+    pub path: String,
+    pub bundle_id: Option<String>,
+    pub version: Option<String>,
+    /// `CFBundleIconFile`, relative to `Contents/Resources`.
+    #[serde(skip)]
+    pub icon_file: Option<String>,
+}
+
+/// Standard app folders, most preferred first. `home` adds `~/Applications`.
+pub fn app_roots(home: Option<&Path>) -> Vec<PathBuf> {
+    let mut roots = vec![PathBuf::from("/Applications"), PathBuf::from("/System/Applications")];
+    if let Some(home) = home {
+        roots.push(home.join("Applications"));
+    }
+    roots
+}
+
+fn plist_string(dict: &plist::Dictionary, key: &str) -> Option<String> {
+    dict.get(key)
+        .and_then(|v| v.as_string())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Read an `.app` bundle's metadata. The name prefers `CFBundleDisplayName`,
+/// then the bundle's file name, then `CFBundleName`. Returns `None` when the
+/// path isn't a bundle.
+pub fn read_bundle(path: &Path) -> Option<InstalledApp> {
+    if path.extension().and_then(|e| e.to_str()) != Some("app") || !path.is_dir() {
+        return None;
+    }
+    let stem = path.file_stem().and_then(|s| s.to_str()).filter(|s| !s.is_empty()).map(str::to_string);
+
+    let info = plist::Value::from_file(path.join("Contents").join("Info.plist")).ok();
+    let dict = info.as_ref().and_then(|v| v.as_dictionary());
+    let field = |key: &str| dict.and_then(|d| plist_string(d, key));
+
+    let name = field("CFBundleDisplayName").or(stem).or_else(|| field("CFBundleName"))?;
+    Some(InstalledApp {
+        name,
+        path: path.to_string_lossy().to_string(),
+        bundle_id: field("CFBundleIdentifier"),
+        version: field("CFBundleShortVersionString").or_else(|| field("CFBundleVersion")),
+        icon_file: field("CFBundleIconFile"),
+    })
+}
+
+fn walk(dir: &Path, depth: usize, out: &mut Vec<InstalledApp>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.extension().and_then(|e| e.to_str()) == Some("app") {
+            // Never descend into a bundle, even to find helper apps inside it.
+            out.extend(read_bundle(&path));
+        } else if depth < MAX_DEPTH && path.is_dir() && !path.is_symlink() {
+            walk(&path, depth + 1, out);
+        }
+    }
+}
+
+/// Find every app bundle under `roots`. Apps are deduplicated by bundle id,
+/// keeping the copy from the earliest root; bundles without an id are kept
+/// unless their path was already seen. Sorted by name.
+pub fn discover(roots: &[PathBuf]) -> Vec<InstalledApp> {
+    let mut found = Vec::new();
+    for root in roots {
+        walk(root, 1, &mut found);
+    }
+
+    let mut seen_ids = HashSet::new();
+    let mut seen_paths = HashSet::new();
+    let mut apps: Vec<InstalledApp> = found
+        .into_iter()
+        .filter(|app| seen_paths.insert(app.path.clone()))
+        .filter(|app| app.bundle_id.as_ref().is_none_or(|id| seen_ids.insert(id.to_lowercase())))
+        .collect();
+    apps.sort_by_key(|a| a.name.to_lowercase());
+    apps
+}
+
+/// The bundle id of the app at `path`, if it is a bundle that has one.
+pub fn bundle_id_at(path: &str) -> Option<String> {
+    read_bundle(Path::new(path)).and_then(|app| app.bundle_id)
+}
+
+/// Where an app lives now. `path` wins while it is still a bundle with
+/// `bundle_id`; otherwise the first installed copy with that id. Without a
+/// bundle id only `path` itself can be checked.
+pub fn resolve<'a>(
+    path: &str,
+    bundle_id: Option<&str>,
+    installed: impl FnOnce() -> &'a [InstalledApp],
+) -> Option<String> {
+    let Some(id) = bundle_id else {
+        return read_bundle(Path::new(path)).map(|_| path.to_string());
+    };
+    if bundle_id_at(path).is_some_and(|found| found.eq_ignore_ascii_case(id)) {
+        return Some(path.to_string());
+    }
+    installed()
+        .iter()
+        .find(|app| app.bundle_id.as_deref().is_some_and(|found| found.eq_ignore_ascii_case(id)))
+        .map(|app| app.path.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/apps")
+    }
+
+    fn fixture(rel: &str) -> PathBuf {
+        fixtures().join(rel)
+    }
+
+    fn discovered() -> Vec<InstalledApp> {
+        discover(&[fixture("primary"), fixture("secondary")])
+    }
+
+    fn names(apps: &[InstalledApp]) -> Vec<&str> {
+        apps.iter().map(|a| a.name.as_str()).collect()
+    }
+
+    #[test]
+    fn reads_xml_info_plist() {
+        let app = read_bundle(&fixture("primary/Alpha.app")).unwrap();
+        assert_eq!(app.name, "Alpha Display");
+        assert_eq!(app.bundle_id.as_deref(), Some("com.example.alpha"));
+        assert_eq!(app.version.as_deref(), Some("1.2"));
+        assert_eq!(app.icon_file.as_deref(), Some("Alpha.icns"));
+    }
+
+    #[test]
+    fn reads_binary_info_plist() {
+        let app = read_bundle(&fixture("primary/Beta.app")).unwrap();
+        assert_eq!(app.bundle_id.as_deref(), Some("com.example.beta"));
+        // No short version string: falls back to CFBundleVersion
+        assert_eq!(app.version.as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn name_fallback_order() {
+        // CFBundleDisplayName first
+        assert_eq!(read_bundle(&fixture("primary/Alpha.app")).unwrap().name, "Alpha Display");
+        // Then the file name, ahead of CFBundleName
+        assert_eq!(read_bundle(&fixture("primary/Beta.app")).unwrap().name, "Beta");
+        // A blank display name counts as missing
+        assert_eq!(read_bundle(&fixture("primary/Blank Display.app")).unwrap().name, "Blank Display");
+        // No Info.plist at all still gives a name, but nothing else
+        let bare = read_bundle(&fixture("primary/NoInfo.app")).unwrap();
+        assert_eq!((bare.name.as_str(), bare.bundle_id), ("NoInfo", None));
+    }
+
+    #[test]
+    fn only_app_directories_are_bundles() {
+        assert!(read_bundle(&fixture("primary/NotAnApp")).is_none());
+        assert!(read_bundle(&fixture("primary/Missing.app")).is_none());
+    }
+
+    #[test]
+    fn nested_depth_limit() {
+        let apps = discovered();
+        let names = names(&apps);
+        assert!(names.contains(&"Delta"));
+        assert!(names.contains(&"Deep"));
+        assert!(!names.contains(&"TooDeep"));
+        // Bundles aren't searched for helpers
+        assert!(names.contains(&"Host"));
+        assert!(!names.contains(&"Helper"));
+    }
+
+    #[test]
+    fn symlinked_folders_are_skipped() {
+        assert!(fixture("primary/Linked").is_symlink());
+        assert!(!names(&discovered()).contains(&"Hidden"));
+    }
+
+    #[test]
+    fn dedupes_by_bundle_id_keeping_earlier_root() {
+        let apps = discovered();
+        let alphas: Vec<&InstalledApp> = apps
+            .iter()
+            .filter(|a| a.bundle_id.as_deref().is_some_and(|id| id.eq_ignore_ascii_case("com.example.alpha")))
+            .collect();
+        assert_eq!(alphas.len(), 1);
+        assert!(alphas[0].path.contains("primary"));
+        // Apps without a bundle id are kept
+        assert!(names(&apps).contains(&"NoInfo"));
+        // Sorted by name, case-insensitively
+        let mut sorted = names(&apps);
+        sorted.sort_by_key(|n| n.to_lowercase());
+        assert_eq!(names(&apps), sorted);
+    }
+
+    #[test]
+    fn resolve_moved_app() {
+        let alpha = fixture("primary/Alpha.app").to_string_lossy().to_string();
+        let gone = fixture("primary/Gone.app").to_string_lossy().to_string();
+        let installed = discovered();
+        let no_scan = || -> &[InstalledApp] { panic!("scanned") };
+
+        assert_eq!(resolve(&alpha, Some("COM.EXAMPLE.ALPHA"), no_scan), Some(alpha.clone()));
+        assert_eq!(resolve(&gone, Some("com.example.alpha"), || &installed), Some(alpha));
+        assert_eq!(resolve(&gone, Some("com.example.unknown"), || &installed), None);
+        assert_eq!(resolve(&gone, None, no_scan), None);
+    }
+}
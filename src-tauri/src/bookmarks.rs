@@ -0,0 +1,205 @@
+//! Bookmarks: URLs, files, folders and apps pinned globally or per desktop.
+//!
+//! Values are validated and normalised before they are saved, so opening a
+//! bookmark only has to hand the value to the opener.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// URL schemes a bookmark may open. Local files use the file kinds instead.
+const URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BookmarkKind {
+    Url,
+    File,
+    Folder,
+    App,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    pub id: String,
+    pub kind: BookmarkKind,
+    pub label: String,
+    /// A URL for `Url`, otherwise an absolute path.
+    pub value: String,
+}
+
+/// Expand a leading `~` and require an absolute path.
+fn absolute_path(value: &str, home: Option<&Path>) -> Result<PathBuf, String> {
+    let path = match value.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            let home = home.ok_or("Home directory unknown")?;
+            match rest.trim_start_matches('/') {
+                "" => home.to_path_buf(),
+                rest => home.join(rest),
+            }
+        }
+        _ => PathBuf::from(value),
+    };
+    if !path.is_absolute() {
+        return Err(format!("'{}' is not an absolute path", value));
+    }
+    Ok(path)
+}
+
+fn is_app_bundle(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("app") && path.is_dir()
+}
+
+/// Check `value` for `kind` and return it normalised: URLs re-serialised,
+/// paths expanded and required to exist with the right type.
+pub fn validate(kind: BookmarkKind, value: &str, home: Option<&Path>) -> Result<String, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("Value is empty".to_string());
+    }
+    if kind == BookmarkKind::Url {
+        let url = tauri::Url::parse(value).map_err(|e| format!("Invalid URL: {}", e))?;
+        if !URL_SCHEMES.contains(&url.scheme()) {
+            return Err(format!("Unsupported URL scheme '{}'", url.scheme()));
+        }
+        if url.scheme() != "mailto" && url.host_str().is_none_or(str::is_empty) {
+            return Err("URL has no host".to_string());
+        }
+        return Ok(url.to_string());
+    }
+
+    let path = absolute_path(value, home)?;
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+    let ok = match kind {
+        BookmarkKind::File => path.is_file(),
+        BookmarkKind::Folder => path.is_dir() && !is_app_bundle(&path),
+        BookmarkKind::App => is_app_bundle(&path),
+        BookmarkKind::Url => unreachable!(),
+    };
+    if !ok {
+        let expected = match kind {
+            BookmarkKind::File => "a file",
+            BookmarkKind::Folder => "a folder",
+            _ => "an app bundle",
+        };
+        return Err(format!("{} is not {}", path.display(), expected));
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// A label for a bookmark saved without one: the host for URLs, otherwise
+/// the file name (without `.app` for apps).
+pub fn default_label(kind: BookmarkKind, value: &str) -> String {
+    let label = match kind {
+        BookmarkKind::Url => tauri::Url::parse(value).ok().and_then(|u| u.host_str().map(str::to_string)),
+        BookmarkKind::App => Path::new(value).file_stem().map(|s| s.to_string_lossy().to_string()),
+        BookmarkKind::File | BookmarkKind::Folder => Path::new(value).file_name().map(|s| s.to_string_lossy().to_string()),
+    };
+    label.unwrap_or_else(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("bookmarks-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// A home directory holding `notes.txt`, `Projects/` and `Tool.app/`.
+    fn home(name: &str) -> TempDir {
+        let dir = TempDir::new(name);
+        fs::write(dir.0.join("notes.txt"), "").unwrap();
+        fs::create_dir(dir.0.join("Projects")).unwrap();
+        fs::create_dir(dir.0.join("Tool.app")).unwrap();
+        dir
+    }
+
+    fn path_in(dir: &TempDir, name: &str) -> String {
+        dir.0.join(name).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn validate_normalises_urls() {
+        let url = |v| validate(BookmarkKind::Url, v, None);
+        assert_eq!(url("  https://Example.com  ").unwrap(), "https://example.com/");
+        assert_eq!(url("http://example.com/a?b#c").unwrap(), "http://example.com/a?b#c");
+        assert_eq!(url("mailto:me@example.com").unwrap(), "mailto:me@example.com");
+    }
+
+    #[test]
+    fn validate_rejects_invalid_urls() {
+        let err = |v| validate(BookmarkKind::Url, v, None).unwrap_err();
+        assert_eq!(err(""), "Value is empty");
+        assert!(err("example.com").starts_with("Invalid URL: "));
+        assert!(err("http://exa mple.com").starts_with("Invalid URL: "));
+        assert_eq!(err("file:///etc/hosts"), "Unsupported URL scheme 'file'");
+        assert_eq!(err("javascript:alert(1)"), "Unsupported URL scheme 'javascript'");
+        assert!(err("https://").starts_with("Invalid URL: "));
+    }
+
+    #[test]
+    fn validate_checks_each_path_kind() {
+        let dir = home("kinds");
+        let file = path_in(&dir, "notes.txt");
+        let folder = path_in(&dir, "Projects");
+        let app = path_in(&dir, "Tool.app");
+        assert_eq!(validate(BookmarkKind::File, &file, None).unwrap(), file);
+        assert_eq!(validate(BookmarkKind::Folder, &folder, None).unwrap(), folder);
+        assert_eq!(validate(BookmarkKind::App, &app, None).unwrap(), app);
+
+        assert_eq!(validate(BookmarkKind::File, &folder, None).unwrap_err(), format!("{} is not a file", folder));
+        assert_eq!(validate(BookmarkKind::Folder, &file, None).unwrap_err(), format!("{} is not a folder", file));
+        // App bundles are folders on disk but aren't folder bookmarks
+        assert_eq!(validate(BookmarkKind::Folder, &app, None).unwrap_err(), format!("{} is not a folder", app));
+        assert_eq!(validate(BookmarkKind::App, &folder, None).unwrap_err(), format!("{} is not an app bundle", folder));
+
+        let missing = path_in(&dir, "missing.txt");
+        assert_eq!(validate(BookmarkKind::File, &missing, None).unwrap_err(), format!("{} does not exist", missing));
+    }
+
+    #[test]
+    fn validate_expands_home_and_rejects_relative_paths() {
+        let dir = home("relative");
+        assert_eq!(validate(BookmarkKind::File, "~/notes.txt", Some(&dir.0)).unwrap(), path_in(&dir, "notes.txt"));
+        assert_eq!(validate(BookmarkKind::Folder, "~", Some(&dir.0)).unwrap(), dir.0.to_string_lossy());
+        assert_eq!(validate(BookmarkKind::File, "~/notes.txt", None).unwrap_err(), "Home directory unknown");
+
+        let err = |v| validate(BookmarkKind::File, v, Some(&dir.0)).unwrap_err();
+        assert_eq!(err("notes.txt"), "'notes.txt' is not an absolute path");
+        assert_eq!(err("./notes.txt"), "'./notes.txt' is not an absolute path");
+        // Only a bare `~` or `~/` is the home directory
+        assert_eq!(err("~other/notes.txt"), "'~other/notes.txt' is not an absolute path");
+    }
+
+    #[test]
+    fn default_label_per_kind() {
+        assert_eq!(default_label(BookmarkKind::Url, "https://docs.example.com/a/b"), "docs.example.com");
+        assert_eq!(default_label(BookmarkKind::File, "/Users/me/notes.txt"), "notes.txt");
+        assert_eq!(default_label(BookmarkKind::Folder, "/Users/me/Projects"), "Projects");
+        assert_eq!(default_label(BookmarkKind::App, "/Applications/Safari.app"), "Safari");
+    }
+
+    #[test]
+    fn default_label_falls_back_to_the_value() {
+        assert_eq!(default_label(BookmarkKind::Url, "mailto:me@example.com"), "mailto:me@example.com");
+        assert_eq!(default_label(BookmarkKind::Url, "not a url"), "not a url");
+        assert_eq!(default_label(BookmarkKind::Folder, "/"), "/");
+    }
+}
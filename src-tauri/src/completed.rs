@@ -0,0 +1,444 @@
+//! The completed-item log: paged queries and the monthly archive.
+//!
+//! Items older than the retention window move out of the data file into one
+//! JSON array per month, e.g. `notes-completed/2026-03.json`. A month file
+//! that exists but can't be read is never overwritten: items that would go
+//! there stay live instead.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CompletedItem {
+    pub id: String,
+    pub text: String,
+    pub desktop_id: i64,
+    #[serde(default)]
+    pub desktop_name: Option<String>,
+    /// RFC 3339.
+    pub completed_at: String,
+}
+
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s).ok().map(|d| d.with_timezone(&Utc))
+}
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Filter for paged completed-log queries. Dates are RFC 3339; `to` is exclusive.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct CompletedQuery {
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub desktop_id: Option<i64>,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CompletedPage {
+    pub items: Vec<CompletedItem>,
+    /// Matching items before paging.
+    pub total: usize,
+}
+
+/// Apply `query` to `items`, newest first. Items completed at the same
+/// instant keep the later one in `items` first; unparseable timestamps sort
+/// last and only match unbounded queries.
+pub fn query(items: &[CompletedItem], query: &CompletedQuery) -> Result<CompletedPage, String> {
+    let bound = |b: &Option<String>| match b {
+        Some(s) => parse_timestamp(s).map(Some).ok_or_else(|| format!("Invalid date '{}'", s)),
+        None => Ok(None),
+    };
+    let from = bound(&query.from)?;
+    let to = bound(&query.to)?;
+
+    let mut matching: Vec<(&CompletedItem, Option<DateTime<Utc>>)> = items
+        .iter()
+        .rev()
+        .filter(|c| query.desktop_id.is_none_or(|d| c.desktop_id == d))
+        .map(|c| (c, parse_timestamp(&c.completed_at)))
+        .filter(|(_, at)| from.is_none_or(|f| at.is_some_and(|a| a >= f)) && to.is_none_or(|t| at.is_some_and(|a| a < t)))
+        .collect();
+    matching.sort_by_key(|&(_, at)| std::cmp::Reverse(at));
+
+    Ok(CompletedPage {
+        total: matching.len(),
+        items: matching
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(DEFAULT_PAGE_SIZE))
+            .map(|(c, _)| c.clone())
+            .collect(),
+    })
+}
+
+/// The archive folder next to the data file: `notes-completed/` (or
+/// `notes-dev-completed/` in debug builds).
+pub fn archive_dir(data_path: &str) -> PathBuf {
+    let data_path = Path::new(data_path);
+    let stem = data_path.file_stem().and_then(|s| s.to_str()).unwrap_or("notes");
+    data_path.with_file_name(format!("{}-completed", stem))
+}
+
+/// Split off items completed before `cutoff`, grouped by "YYYY-MM".
+/// Items with unparseable timestamps stay live.
+pub fn split_expired(items: Vec<CompletedItem>, cutoff: DateTime<Utc>) -> (Vec<CompletedItem>, BTreeMap<String, Vec<CompletedItem>>) {
+    let mut live = Vec::new();
+    let mut expired: BTreeMap<String, Vec<CompletedItem>> = BTreeMap::new();
+    for item in items {
+        match parse_timestamp(&item.completed_at) {
+            Some(at) if at < cutoff => expired.entry(at.format("%Y-%m").to_string()).or_default().push(item),
+            _ => live.push(item),
+        }
+    }
+    (live, expired)
+}
+
+/// Move items in `live` completed before `cutoff` into their month files in
+/// `dir`, merging with what's already there. Returns whether `live` changed.
+pub fn archive_expired(dir: &Path, live: &mut Vec<CompletedItem>, cutoff: DateTime<Utc>) -> bool {
+    let (kept, expired) = split_expired(std::mem::take(live), cutoff);
+    *live = kept;
+    if expired.is_empty() {
+        return false;
+    }
+
+    fs::create_dir_all(dir).ok();
+    for (month, mut items) in expired {
+        let file = dir.join(format!("{}.json", month));
+        // Keep the items live rather than lose them, and never overwrite a
+        // month file we couldn't read.
+        let result = read_month(&file).and_then(|mut archived| {
+            archived.extend(items.iter().cloned());
+            archived.sort_by(|a, b| a.completed_at.cmp(&b.completed_at));
+            write_month(&file, &archived)
+        });
+        match result {
+            Ok(()) => log::info!("[completed] archived {} item(s) to {}", items.len(), file.display()),
+            Err(e) => {
+                log::error!("[completed] skipping archive for {}: {}", month, e);
+                live.append(&mut items);
+            }
+        }
+    }
+    live.sort_by(|a, b| a.completed_at.cmp(&b.completed_at));
+    true
+}
+
+/// Read one month's archive. A missing file is an empty month; a file that
+/// exists but can't be read or parsed is an error.
+pub fn read_month(file: &Path) -> Result<Vec<CompletedItem>, String> {
+    match fs::read_to_string(file) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| format!("{}: {}", file.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("{}: {}", file.display(), e)),
+    }
+}
+
+/// Replace one month's archive, via a temporary file so a failed write
+/// leaves the old file intact. An empty month removes the file.
+pub fn write_month(file: &Path, items: &[CompletedItem]) -> Result<(), String> {
+    if items.is_empty() {
+        return match fs::remove_file(file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("{}: {}", file.display(), e)),
+            _ => Ok(()),
+        };
+    }
+    let json = serde_json::to_string_pretty(items).map_err(|e| e.to_string())?;
+    let tmp = file.with_extension("json.tmp");
+    fs::write(&tmp, json).and_then(|()| fs::rename(&tmp, file)).map_err(|e| format!("{}: {}", file.display(), e))
+}
+
+/// Month files in `dir` for `[from_month, to_month]` (inclusive, "YYYY-MM";
+/// `None` is unbounded), oldest first.
+pub fn months(dir: &Path, from_month: Option<&str>, to_month: Option<&str>) -> Vec<(String, PathBuf)> {
+    let mut months: Vec<(String, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+                .filter_map(|p| Some((p.file_stem()?.to_str()?.to_string(), p)))
+                .filter(|(m, _)| from_month.is_none_or(|f| m.as_str() >= f) && to_month.is_none_or(|t| m.as_str() <= t))
+                .collect()
+        })
+        .unwrap_or_default();
+    months.sort();
+    months
+}
+
+/// Read archived items for months in `[from_month, to_month]`, oldest first.
+/// Unreadable months are logged and skipped.
+pub fn load_archived(dir: &Path, from_month: Option<&str>, to_month: Option<&str>) -> Vec<CompletedItem> {
+    months(dir, from_month, to_month)
+        .into_iter()
+        .flat_map(|(_, file)| {
+            read_month(&file).unwrap_or_else(|e| {
+                log::warn!("[completed] skipping unreadable archive: {}", e);
+                Vec::new()
+            })
+        })
+        .collect()
+}
+
+/// Find archived item `id` and apply `f` to its month (the items and the
+/// item's index), then write the month back. `Ok(None)` if no readable
+/// month has the item.
+pub fn update_archived<T>(
+    dir: &Path,
+    id: &str,
+    f: impl FnOnce(&mut Vec<CompletedItem>, usize) -> T,
+) -> Result<Option<T>, String> {
+    for (_, file) in months(dir, None, None) {
+        let Ok(mut items) = read_month(&file) else {
+            continue;
+        };
+        if let Some(index) = items.iter().position(|c| c.id == id) {
+            let result = f(&mut items, index);
+            write_month(&file, &items)?;
+            return Ok(Some(result));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch folder, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("completed-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn item(id: &str, completed_at: &str) -> CompletedItem {
+        CompletedItem {
+            id: id.to_string(),
+            text: format!("task {}", id),
+            desktop_id: 1,
+            desktop_name: None,
+            completed_at: completed_at.to_string(),
+        }
+    }
+
+    fn ids(items: &[CompletedItem]) -> Vec<&str> {
+        items.iter().map(|c| c.id.as_str()).collect()
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        parse_timestamp(s).unwrap()
+    }
+
+    fn on_desktop(mut item: CompletedItem, desktop_id: i64) -> CompletedItem {
+        item.desktop_id = desktop_id;
+        item
+    }
+
+    fn page(items: &[CompletedItem], q: CompletedQuery) -> (Vec<String>, usize) {
+        let page = query(items, &q).unwrap();
+        (page.items.into_iter().map(|c| c.id).collect(), page.total)
+    }
+
+    fn log() -> Vec<CompletedItem> {
+        vec![
+            item("a", "2026-01-05T10:00:00Z"),
+            on_desktop(item("b", "2026-01-20T10:00:00Z"), 2),
+            item("c", "2026-02-01T00:00:00Z"),
+            on_desktop(item("d", "2026-02-10T08:00:00+02:00"), 2),
+            item("e", "2026-03-01T00:00:00Z"),
+        ]
+    }
+
+    #[test]
+    fn query_returns_newest_first() {
+        let (ids, total) = page(&log(), CompletedQuery::default());
+        assert_eq!((ids, total), (vec!["e".into(), "d".into(), "c".into(), "b".into(), "a".into()], 5));
+
+        // Out-of-order input (e.g. an archive next to live items) is sorted;
+        // ties keep the later item first; garbled timestamps go last
+        let mixed = vec![
+            item("late", "2026-03-01T00:00:00Z"),
+            item("garbled", "soon"),
+            item("early", "2026-01-01T00:00:00Z"),
+            item("tie-1", "2026-02-01T00:00:00Z"),
+            item("tie-2", "2026-02-01T01:00:00+01:00"),
+        ];
+        assert_eq!(page(&mixed, CompletedQuery::default()).0, ["late", "tie-2", "tie-1", "early", "garbled"]);
+    }
+
+    #[test]
+    fn query_pages_after_filtering() {
+        let q = |offset, limit| CompletedQuery { offset, limit: Some(limit), ..Default::default() };
+        assert_eq!(page(&log(), q(0, 2)), (vec!["e".into(), "d".into()], 5));
+        assert_eq!(page(&log(), q(2, 2)), (vec!["c".into(), "b".into()], 5));
+        assert_eq!(page(&log(), q(4, 2)), (vec!["a".into()], 5));
+        assert_eq!(page(&log(), q(9, 2)), (vec![], 5));
+
+        let many: Vec<CompletedItem> = (0..60).map(|i| item(&i.to_string(), &format!("2026-01-01T00:{:02}:00Z", i))).collect();
+        assert_eq!(query(&many, &CompletedQuery::default()).unwrap().items.len(), DEFAULT_PAGE_SIZE);
+    }
+
+    #[test]
+    fn query_filters_by_desktop_and_dates() {
+        let by_desktop = CompletedQuery { desktop_id: Some(2), ..Default::default() };
+        assert_eq!(page(&log(), by_desktop), (vec!["d".into(), "b".into()], 2));
+
+        // `from` is inclusive, `to` exclusive, both compared as instants
+        let february = CompletedQuery {
+            from: Some("2026-02-01T00:00:00Z".into()),
+            to: Some("2026-03-01T00:00:00Z".into()),
+            ..Default::default()
+        };
+        assert_eq!(page(&log(), february), (vec!["d".into(), "c".into()], 2));
+        let local = CompletedQuery { to: Some("2026-02-10T06:00:01Z".into()), from: Some("2026-02-10T06:00:00Z".into()), ..Default::default() };
+        assert_eq!(page(&log(), local).0, ["d"]);
+
+        let combined = CompletedQuery { desktop_id: Some(2), from: Some("2026-02-01T00:00:00Z".into()), ..Default::default() };
+        assert_eq!(page(&log(), combined).0, ["d"]);
+
+        let garbled = vec![item("x", "whenever")];
+        assert_eq!(page(&garbled, CompletedQuery { from: Some("2000-01-01T00:00:00Z".into()), ..Default::default() }).1, 0);
+    }
+
+    #[test]
+    fn query_rejects_invalid_bounds() {
+        let q = CompletedQuery { from: Some("last week".into()), ..Default::default() };
+        assert_eq!(query(&log(), &q).unwrap_err(), "Invalid date 'last week'");
+    }
+
+    #[test]
+    fn archive_dir_sits_next_to_the_data_file() {
+        assert_eq!(archive_dir("/data/notes.json"), Path::new("/data/notes-completed"));
+        assert_eq!(archive_dir("/data/notes-dev.json"), Path::new("/data/notes-dev-completed"));
+    }
+
+    #[test]
+    fn retention_boundary_is_exclusive() {
+        let cutoff = at("2026-03-10T00:00:00Z");
+        let items = vec![
+            item("before", "2026-03-09T23:59:59Z"),
+            item("at", "2026-03-10T00:00:00Z"),
+            item("after", "2026-03-10T00:00:01Z"),
+            // Offsets are compared as instants: 01:00+02:00 is 23:00 the day before
+            item("offset", "2026-03-10T01:00:00+02:00"),
+            item("garbled", "yesterday"),
+        ];
+        let (live, expired) = split_expired(items, cutoff);
+        assert_eq!(ids(&live), ["at", "after", "garbled"]);
+        assert_eq!(ids(&expired["2026-03"]), ["before", "offset"]);
+    }
+
+    #[test]
+    fn expired_items_are_bucketed_by_utc_month() {
+        let items = vec![
+            item("jan", "2026-01-31T23:30:00Z"),
+            item("feb-local", "2026-02-01T00:30:00+01:00"),
+            item("feb", "2026-02-14T12:00:00Z"),
+            item("dec", "2025-12-01T00:00:00Z"),
+        ];
+        let (live, expired) = split_expired(items, at("2026-03-01T00:00:00Z"));
+        assert!(live.is_empty());
+        assert_eq!(expired.keys().collect::<Vec<_>>(), ["2025-12", "2026-01", "2026-02"]);
+        assert_eq!(ids(&expired["2026-01"]), ["jan", "feb-local"]);
+        assert_eq!(ids(&expired["2026-02"]), ["feb"]);
+    }
+
+    #[test]
+    fn archiving_merges_into_existing_month() {
+        let dir = TempDir::new("merge");
+        let march = dir.0.join("2026-03.json");
+        write_month(&march, &[item("old-1", "2026-03-01T00:00:00Z"), item("old-3", "2026-03-20T00:00:00Z")]).unwrap();
+
+        let mut live = vec![item("new-2", "2026-03-10T00:00:00Z"), item("recent", "2026-05-01T00:00:00Z")];
+        assert!(archive_expired(&dir.0, &mut live, at("2026-04-01T00:00:00Z")));
+
+        assert_eq!(ids(&live), ["recent"]);
+        assert_eq!(ids(&read_month(&march).unwrap()), ["old-1", "new-2", "old-3"]);
+        assert!(!dir.0.join("2026-03.json.tmp").exists());
+
+        // Nothing left to archive
+        assert!(!archive_expired(&dir.0, &mut live, at("2026-04-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn unreadable_month_is_never_overwritten() {
+        let dir = TempDir::new("corrupt");
+        let march = dir.0.join("2026-03.json");
+        fs::write(&march, "{ not json").unwrap();
+
+        let mut live = vec![item("march", "2026-03-10T00:00:00Z"), item("feb", "2026-02-10T00:00:00Z")];
+        assert!(archive_expired(&dir.0, &mut live, at("2026-04-01T00:00:00Z")));
+
+        // March stays live and its file is untouched; February still archives
+        assert_eq!(ids(&live), ["march"]);
+        assert_eq!(fs::read_to_string(&march).unwrap(), "{ not json");
+        assert_eq!(ids(&read_month(&dir.0.join("2026-02.json")).unwrap()), ["feb"]);
+        assert!(read_month(&march).is_err());
+    }
+
+    #[test]
+    fn missing_month_reads_as_empty_and_empty_month_removes_the_file() {
+        let dir = TempDir::new("empty");
+        let file = dir.0.join("2026-01.json");
+        assert_eq!(read_month(&file).unwrap(), Vec::new());
+        write_month(&file, &[item("a", "2026-01-01T00:00:00Z")]).unwrap();
+        write_month(&file, &[]).unwrap();
+        assert!(!file.exists());
+        write_month(&file, &[]).unwrap();
+    }
+
+    #[test]
+    fn months_are_filtered_and_sorted() {
+        let dir = TempDir::new("months");
+        for month in ["2026-03", "2025-12", "2026-01"] {
+            write_month(&dir.0.join(format!("{}.json", month)), &[item(month, &format!("{}-01T00:00:00Z", month))]).unwrap();
+        }
+        fs::write(dir.0.join("notes.txt"), "").unwrap();
+        fs::write(dir.0.join("2026-02.json"), "[oops").unwrap();
+
+        let names = |from, to| months(&dir.0, from, to).into_iter().map(|(m, _)| m).collect::<Vec<_>>();
+        assert_eq!(names(None, None), ["2025-12", "2026-01", "2026-02", "2026-03"]);
+        assert_eq!(names(Some("2026-01"), Some("2026-02")), ["2026-01", "2026-02"]);
+        // The unreadable month is skipped when loading
+        assert_eq!(ids(&load_archived(&dir.0, None, None)), ["2025-12", "2026-01", "2026-03"]);
+    }
+
+    #[test]
+    fn update_archived_rewrites_only_the_matching_month() {
+        let dir = TempDir::new("update");
+        let jan = dir.0.join("2026-01.json");
+        write_month(&jan, &[item("a", "2026-01-01T00:00:00Z"), item("b", "2026-01-02T00:00:00Z")]).unwrap();
+
+        let removed = update_archived(&dir.0, "b", |items, index| items.remove(index)).unwrap();
+        assert_eq!(removed.map(|c| c.id), Some("b".to_string()));
+        assert_eq!(ids(&read_month(&jan).unwrap()), ["a"]);
+        assert!(update_archived(&dir.0, "zzz", |_, _| ()).unwrap().is_none());
+
+        // Removing the last item removes the month
+        update_archived(&dir.0, "a", |items, index| items.remove(index)).unwrap();
+        assert!(!jan.exists());
+    }
+}
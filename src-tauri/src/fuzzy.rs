@@ -0,0 +1,148 @@
+//! Small subsequence fuzzy matcher for finding desktops by name, title or
+//! todo text.
+//!
+//! Every query character must appear in order in the text. Matches score
+//! higher when they start words, run consecutively and leave few gaps, so
+//! "cm" ranks "Context Maintainer" above "Documents".
+
+/// Points for every matched character.
+const SCORE_MATCH: i64 = 16;
+/// Extra points when a match starts a word (after a separator or a
+/// lower→upper case change, or at the start of the text).
+const BONUS_BOUNDARY: i64 = 8;
+/// Extra points when a match directly follows the previous one.
+const BONUS_CONSECUTIVE: i64 = 12;
+/// Cost of each skipped character between two matches.
+const PENALTY_GAP: i64 = 1;
+
+/// Score `query` against `text`, case-insensitively. Whitespace splits the
+/// query into words that are matched independently, and all of them must
+/// match. Returns `None` when something doesn't match or the query is empty.
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let mut words = query.split_whitespace().peekable();
+    words.peek()?;
+    words.map(|word| score_word(word, text)).sum()
+}
+
+fn score_word(word: &str, text: &str) -> Option<i64> {
+    let needle: Vec<char> = word.chars().flat_map(char::to_lowercase).collect();
+    let original: Vec<char> = text.chars().collect();
+    let haystack: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+
+    let bonus: Vec<i64> = (0..original.len())
+        .map(|j| {
+            let starts_word = match j.checked_sub(1).map(|p| original[p]) {
+                None => true,
+                Some(prev) => !prev.is_alphanumeric() || (prev.is_lowercase() && original[j].is_uppercase()),
+            };
+            if starts_word { BONUS_BOUNDARY } else { 0 }
+        })
+        .collect();
+
+    // best[j]: best score with the current needle char matched at text[j]
+    let mut best: Vec<Option<i64>> = haystack
+        .iter()
+        .enumerate()
+        .map(|(j, &c)| (c == needle[0]).then(|| SCORE_MATCH + bonus[j]))
+        .collect();
+
+    for &n in &needle[1..] {
+        let mut next = vec![None; haystack.len()];
+        // Best previous match ending at least two chars back, less the gap.
+        let mut gapped: Option<i64> = None;
+        for j in 1..haystack.len() {
+            if j >= 2 {
+                let candidate = best[j - 2].map(|s| s - PENALTY_GAP);
+                gapped = gapped.map(|g| g - PENALTY_GAP).max(candidate);
+            }
+            if haystack[j] != n {
+                continue;
+            }
+            let consecutive = best[j - 1].map(|s| s + BONUS_CONSECUTIVE);
+            next[j] = consecutive.max(gapped).map(|s| s + SCORE_MATCH + bonus[j]);
+        }
+        best = next;
+    }
+
+    best.into_iter().flatten().max()
+}
+
+/// Whether the top score is clearly ahead of the runner-up. `scores` must be
+/// sorted in descending order.
+pub fn is_decisive(scores: &[i64]) -> bool {
+    match scores {
+        [] => false,
+        [_] => true,
+        [first, second, ..] => first - second >= SCORE_MATCH,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Texts sorted by score for `query`, best first; non-matches dropped.
+    fn ranked<'a>(query: &str, texts: &[&'a str]) -> Vec<&'a str> {
+        let mut scored: Vec<(i64, &str)> = texts.iter().filter_map(|t| Some((score(query, t)?, *t))).collect();
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, t)| t).collect()
+    }
+
+    #[test]
+    fn requires_every_char_in_order() {
+        assert!(score("ctx", "Context").is_some());
+        assert!(score("xtc", "Context").is_none());
+        assert!(score("contexts", "Context").is_none());
+        assert!(score("", "Context").is_none());
+        assert!(score("   ", "Context").is_none());
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert_eq!(score("MAIL", "mail"), score("mail", "Mail"));
+        assert!(score("ÄB", "äbc").is_some());
+    }
+
+    #[test]
+    fn prefix_beats_later_match() {
+        assert_eq!(ranked("mai", &["Email", "Main"]), vec!["Main", "Email"]);
+        assert!(score("doc", "Docs").unwrap() > score("doc", "undocumented").unwrap());
+    }
+
+    #[test]
+    fn word_boundaries_score_higher() {
+        // Both words start with the query chars in the first text only
+        assert_eq!(ranked("cm", &["Documents", "Context Maintainer"]), vec!["Context Maintainer", "Documents"]);
+        // camelCase and separators count as boundaries
+        assert!(score("ts", "todoStore").unwrap() > score("ts", "lists").unwrap());
+        assert!(score("pn", "project-notes").unwrap() > score("pn", "pentagon").unwrap());
+    }
+
+    #[test]
+    fn consecutive_beats_scattered() {
+        assert!(score("abc", "abcxyz").unwrap() > score("abc", "axbxcx").unwrap());
+        // Fewer gaps score higher
+        assert!(score("ac", "abc").unwrap() > score("ac", "abbbbc").unwrap());
+    }
+
+    #[test]
+    fn query_words_match_independently() {
+        assert!(score("notes work", "Work notes").is_some());
+        assert!(score("notes play", "Work notes").is_none());
+        assert_eq!(score("a b", "ab"), Some(score("a", "ab").unwrap() + score("b", "ab").unwrap()));
+    }
+
+    #[test]
+    fn decisive_needs_a_clear_lead() {
+        assert!(!is_decisive(&[]));
+        assert!(is_decisive(&[5]));
+        assert!(is_decisive(&[40, 40 - SCORE_MATCH]));
+        assert!(!is_decisive(&[40, 40 - SCORE_MATCH + 1]));
+    }
+}
@@ -0,0 +1,189 @@
+//! Browser-style back/forward history of visited desktops on one display.
+
+use serde::Serialize;
+
+/// Oldest entries are dropped beyond this.
+const MAX_ENTRIES: usize = 50;
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NavHistory {
+    /// Visited desktop ids, oldest first.
+    pub entries: Vec<i64>,
+    /// Index of the current desktop in `entries`.
+    pub cursor: usize,
+}
+
+impl NavHistory {
+    pub fn current(&self) -> Option<i64> {
+        self.entries.get(self.cursor).copied()
+    }
+
+    /// Record a visit. Revisiting the current desktop is a no-op; anything
+    /// else drops the forward entries, like following a link in a browser.
+    /// Returns whether the history changed.
+    pub fn visit(&mut self, id: i64) -> bool {
+        if self.current() == Some(id) {
+            return false;
+        }
+        self.entries.truncate(self.cursor + 1);
+        self.entries.push(id);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+        self.cursor = self.entries.len() - 1;
+        true
+    }
+
+    fn index(&self, offset: isize) -> Option<usize> {
+        self.cursor
+            .checked_add_signed(offset)
+            .filter(|&i| offset != 0 && i < self.entries.len())
+    }
+
+    /// The entry `offset` steps back (negative) or forward (positive).
+    pub fn peek(&self, offset: isize) -> Option<i64> {
+        self.index(offset).map(|i| self.entries[i])
+    }
+
+    /// Move the cursor by `offset` once the switch there has landed.
+    pub fn go(&mut self, offset: isize) -> Option<i64> {
+        let i = self.index(offset)?;
+        self.cursor = i;
+        Some(self.entries[i])
+    }
+
+    /// Drop the entry `offset` steps away (e.g. a desktop that no longer
+    /// exists), keeping the cursor on the current desktop.
+    pub fn remove(&mut self, offset: isize) {
+        if let Some(i) = self.index(offset) {
+            self.entries.remove(i);
+            if i < self.cursor {
+                self.cursor -= 1;
+            }
+            // Dropping an entry can leave the same desktop twice in a row.
+            if i > 0 && i < self.entries.len() && self.entries[i - 1] == self.entries[i] {
+                self.entries.remove(i);
+                if i <= self.cursor {
+                    self.cursor -= 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(ids: &[i64]) -> NavHistory {
+        let mut h = NavHistory::default();
+        for &id in ids {
+            h.visit(id);
+        }
+        h
+    }
+
+    #[test]
+    fn visit_skips_the_current_desktop() {
+        let mut h = history(&[1, 2]);
+        assert!(!h.visit(2));
+        assert!(h.visit(1));
+        assert_eq!((h.entries.as_slice(), h.cursor), (&[1, 2, 1][..], 2));
+    }
+
+    #[test]
+    fn visit_caps_the_history() {
+        let h = history(&(0..60).collect::<Vec<_>>());
+        assert_eq!(h.entries.len(), MAX_ENTRIES);
+        assert_eq!(h.entries[0], 10);
+        assert_eq!(h.cursor, MAX_ENTRIES - 1);
+        assert_eq!(h.current(), Some(59));
+    }
+
+    #[test]
+    fn peek_looks_without_moving() {
+        let h = history(&[1, 2, 3, 4]);
+        assert_eq!(h.peek(-1), Some(3));
+        assert_eq!(h.peek(-3), Some(1));
+        assert_eq!(h.peek(-4), None);
+        assert_eq!(h.peek(1), None);
+        assert_eq!(h.peek(0), None);
+        assert_eq!(h.cursor, 3);
+        assert_eq!(NavHistory::default().peek(-1), None);
+    }
+
+    #[test]
+    fn back_and_forward_stop_at_the_ends() {
+        let mut h = history(&[1, 2, 3]);
+        assert_eq!(h.go(1), None);
+        assert_eq!(h.go(-1), Some(2));
+        assert_eq!(h.go(-1), Some(1));
+        assert_eq!(h.go(-1), None);
+        assert_eq!(h.cursor, 0);
+        assert_eq!(h.go(2), Some(3));
+        assert_eq!(h.go(1), None);
+        assert_eq!(h.current(), Some(3));
+    }
+
+    #[test]
+    fn visiting_after_going_back_drops_forward_entries() {
+        let mut h = history(&[1, 2, 3, 4]);
+        h.go(-2);
+        assert!(h.visit(5));
+        assert_eq!(h.entries, vec![1, 2, 5]);
+        assert_eq!(h.cursor, 2);
+        assert_eq!(h.peek(1), None);
+        // Revisiting the entry we went back to keeps the forward entries
+        h.go(-1);
+        assert!(!h.visit(2));
+        assert_eq!(h.peek(1), Some(5));
+    }
+
+    #[test]
+    fn remove_before_the_cursor_shifts_it() {
+        let mut h = history(&[1, 2, 3, 4]);
+        h.remove(-2);
+        assert_eq!(h.entries, vec![1, 3, 4]);
+        assert_eq!(h.current(), Some(4));
+        assert_eq!(h.peek(-1), Some(3));
+    }
+
+    #[test]
+    fn remove_after_the_cursor_keeps_it() {
+        let mut h = history(&[1, 2, 3, 4]);
+        h.go(-3);
+        h.remove(2);
+        assert_eq!(h.entries, vec![1, 2, 4]);
+        assert_eq!((h.cursor, h.current()), (0, Some(1)));
+    }
+
+    #[test]
+    fn remove_at_the_cursor_or_out_of_range_is_a_no_op() {
+        let mut h = history(&[1, 2, 3]);
+        h.remove(0);
+        h.remove(1);
+        h.remove(-3);
+        assert_eq!(h.entries, vec![1, 2, 3]);
+        assert_eq!(h.cursor, 2);
+    }
+
+    #[test]
+    fn remove_merges_repeated_neighbours() {
+        // Behind the cursor
+        let mut h = history(&[1, 2, 1, 3]);
+        h.remove(-2);
+        assert_eq!(h.entries, vec![1, 3]);
+        assert_eq!(h.current(), Some(3));
+
+        // Next to the cursor, going back
+        let mut h = history(&[1, 2, 1]);
+        h.remove(-1);
+        assert_eq!((h.entries.as_slice(), h.cursor), (&[1][..], 0));
+
+        // Next to the cursor, going forward
+        let mut h = history(&[1, 2, 1]);
+        h.go(-2);
+        h.remove(1);
+        assert_eq!((h.entries.as_slice(), h.cursor), (&[1][..], 0));
+    }
+}
@@ -0,0 +1,274 @@
+//! Global hotkey bindings: parsing, normalisation and conflict checks.
+//!
+//! Accelerators are written like `Ctrl+Alt+K` or `Cmd+Shift+Space`. They are
+//! normalised to a canonical `Ctrl+Alt+Shift+Cmd+Key` form, which is also a
+//! string the global-shortcut plugin accepts.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// Show or hide every window, like the tray's "Hide Entirely".
+    ToggleWindows,
+    /// Add a todo on the current desktop.
+    QuickAddTodo,
+    /// Start the first timer preset.
+    StartTimer,
+    /// Return to the previously visited desktop.
+    PreviousDesktop,
+    /// Show the desktops panel and focus the current desktop, so arrow keys
+    /// and Enter switch desktops.
+    OpenSwitcher,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Accelerator {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub cmd: bool,
+    /// Canonical key name, e.g. `K`, `5`, `F6`, `Space`, `Comma`.
+    pub key: String,
+}
+
+const NAMED_KEYS: &[(&str, &[&str])] = &[
+    ("Space", &["SPACE"]),
+    ("Tab", &["TAB"]),
+    ("Enter", &["ENTER", "RETURN"]),
+    ("Escape", &["ESCAPE", "ESC"]),
+    ("Backspace", &["BACKSPACE"]),
+    ("Delete", &["DELETE"]),
+    ("Up", &["UP", "ARROWUP"]),
+    ("Down", &["DOWN", "ARROWDOWN"]),
+    ("Left", &["LEFT", "ARROWLEFT"]),
+    ("Right", &["RIGHT", "ARROWRIGHT"]),
+    ("Home", &["HOME"]),
+    ("End", &["END"]),
+    ("PageUp", &["PAGEUP"]),
+    ("PageDown", &["PAGEDOWN"]),
+    ("Comma", &["COMMA", ","]),
+    ("Period", &["PERIOD", "."]),
+    ("Slash", &["SLASH", "/"]),
+    ("Semicolon", &["SEMICOLON", ";"]),
+    ("Quote", &["QUOTE", "'"]),
+    ("BracketLeft", &["BRACKETLEFT", "["]),
+    ("BracketRight", &["BRACKETRIGHT", "]"]),
+    ("Backslash", &["BACKSLASH", "\\"]),
+    ("Minus", &["MINUS", "-"]),
+    ("Equal", &["EQUAL", "="]),
+    ("Backquote", &["BACKQUOTE", "`"]),
+];
+
+/// Shortcuts macOS or this app already rely on. Ctrl+Arrow and Ctrl+1–9 are
+/// the Mission Control shortcuts desktop switching posts.
+const RESERVED: &[&str] = &[
+    "Cmd+Tab", "Cmd+Space", "Cmd+Q", "Cmd+W", "Cmd+H", "Cmd+M",
+    "Cmd+A", "Cmd+C", "Cmd+V", "Cmd+X", "Cmd+Z", "Cmd+S",
+    "Ctrl+Left", "Ctrl+Right", "Ctrl+Up", "Ctrl+Down",
+    "Ctrl+1", "Ctrl+2", "Ctrl+3", "Ctrl+4", "Ctrl+5", "Ctrl+6", "Ctrl+7", "Ctrl+8", "Ctrl+9",
+];
+
+fn parse_key(token: &str) -> Option<String> {
+    let upper = token.to_ascii_uppercase();
+    let mut chars = upper.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphanumeric() {
+            return Some(c.to_string());
+        }
+    }
+    if let Some(n) = upper.strip_prefix('F').and_then(|n| n.parse::<u8>().ok()) {
+        if (1..=20).contains(&n) {
+            return Some(format!("F{}", n));
+        }
+    }
+    NAMED_KEYS
+        .iter()
+        .find(|(_, aliases)| aliases.contains(&upper.as_str()))
+        .map(|(name, _)| name.to_string())
+}
+
+impl Accelerator {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut accel = Accelerator { ctrl: false, alt: false, shift: false, cmd: false, key: String::new() };
+        let mut key = None;
+
+        for token in text.split('+').map(str::trim) {
+            if token.is_empty() {
+                return Err(format!("Invalid shortcut '{}'", text));
+            }
+            let flag = match token.to_ascii_uppercase().as_str() {
+                "CTRL" | "CONTROL" | "⌃" => &mut accel.ctrl,
+                "ALT" | "OPTION" | "OPT" | "⌥" => &mut accel.alt,
+                "SHIFT" | "⇧" => &mut accel.shift,
+                "CMD" | "COMMAND" | "SUPER" | "⌘" => &mut accel.cmd,
+                _ => {
+                    if key.is_some() {
+                        return Err(format!("'{}' has more than one key", text));
+                    }
+                    key = Some(parse_key(token).ok_or_else(|| format!("Unknown key '{}'", token))?);
+                    continue;
+                }
+            };
+            if *flag {
+                return Err(format!("'{}' repeats a modifier", text));
+            }
+            *flag = true;
+        }
+
+        accel.key = key.ok_or_else(|| format!("'{}' has no key", text))?;
+        let is_function_key = accel.key.len() > 1 && accel.key.starts_with('F') && accel.key[1..].parse::<u8>().is_ok();
+        if !(is_function_key || accel.ctrl || accel.alt || accel.cmd) {
+            return Err(format!("'{}' needs Ctrl, Alt or Cmd", text));
+        }
+        Ok(accel)
+    }
+
+    pub fn is_reserved(&self) -> bool {
+        let text = self.to_string();
+        RESERVED.contains(&text.as_str())
+    }
+}
+
+impl std::fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (on, name) in [(self.ctrl, "Ctrl"), (self.alt, "Alt"), (self.shift, "Shift"), (self.cmd, "Cmd")] {
+            if on {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/// A binding that couldn't be saved or registered.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct HotkeyError {
+    pub action: HotkeyAction,
+    pub accelerator: String,
+    pub message: String,
+}
+
+/// Parse every non-empty binding and check for reserved or duplicate
+/// shortcuts. Returns the valid bindings and an error for each rejected one.
+pub fn validate(bindings: &HashMap<HotkeyAction, String>) -> (Vec<(HotkeyAction, Accelerator)>, Vec<HotkeyError>) {
+    let mut sorted: Vec<(&HotkeyAction, &String)> = bindings.iter().filter(|(_, a)| !a.trim().is_empty()).collect();
+    sorted.sort();
+
+    let mut valid: Vec<(HotkeyAction, Accelerator)> = Vec::new();
+    let mut errors = Vec::new();
+    for (&action, text) in sorted {
+        let error = |message: String| HotkeyError { action, accelerator: text.clone(), message };
+        match Accelerator::parse(text) {
+            Err(message) => errors.push(error(message)),
+            Ok(accel) if accel.is_reserved() => errors.push(error(format!("{} is reserved by macOS", accel))),
+            Ok(accel) => match valid.iter().find(|(_, existing)| *existing == accel) {
+                Some((owner, _)) => errors.push(error(format!("{} is already used by {:?}", accel, owner))),
+                None => valid.push((action, accel)),
+            },
+        }
+    }
+    (valid, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Accelerator {
+        Accelerator::parse(text).unwrap()
+    }
+
+    fn bindings(pairs: &[(HotkeyAction, &str)]) -> HashMap<HotkeyAction, String> {
+        pairs.iter().map(|&(action, text)| (action, text.to_string())).collect()
+    }
+
+    #[test]
+    fn modifier_order_does_not_matter() {
+        assert_eq!(parse("Shift+Cmd+Ctrl+Alt+K"), parse("Ctrl+Alt+Shift+Cmd+K"));
+        assert_eq!(parse("Shift+Cmd+Ctrl+Alt+K").to_string(), "Ctrl+Alt+Shift+Cmd+K");
+    }
+
+    #[test]
+    fn aliases_and_case_are_normalised() {
+        assert_eq!(parse("control+option+k"), parse("Ctrl+Alt+K"));
+        assert_eq!(parse("⌘+⇧+space"), parse("Cmd+Shift+Space"));
+        assert_eq!(parse("super+,").key, "Comma");
+        assert_eq!(parse("Opt+ArrowUp").key, "Up");
+        assert_eq!(parse("cmd + return").key, "Enter");
+        assert_eq!(parse("f6").to_string(), "F6");
+    }
+
+    #[test]
+    fn rejects_malformed_accelerators() {
+        for (text, message) in [
+            ("", "Invalid"),
+            ("Ctrl+", "Invalid"),
+            ("Ctrl++K", "Invalid"),
+            ("Ctrl+Alt", "has no key"),
+            ("Ctrl+K+J", "more than one key"),
+            ("Ctrl+Ctrl+K", "repeats a modifier"),
+            ("Ctrl+Hyper", "Unknown key"),
+            ("Ctrl+F21", "Unknown key"),
+            ("Shift+K", "needs Ctrl, Alt or Cmd"),
+            ("K", "needs Ctrl, Alt or Cmd"),
+        ] {
+            let error = Accelerator::parse(text).unwrap_err();
+            assert!(error.contains(message), "{:?}: {}", text, error);
+        }
+        // Function keys don't need a modifier
+        assert!(Accelerator::parse("F12").is_ok());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in ["Ctrl+Alt+K", "Shift+Cmd+Space", "Alt+5", "F1", "Ctrl+Shift+Backquote", "Cmd+BracketLeft"] {
+            let accel = parse(text);
+            assert_eq!(accel.to_string(), text);
+            assert_eq!(parse(&accel.to_string()), accel);
+        }
+    }
+
+    #[test]
+    fn reserved_shortcuts() {
+        assert!(parse("cmd+tab").is_reserved());
+        assert!(parse("Ctrl+ArrowLeft").is_reserved());
+        assert!(parse("Ctrl+3").is_reserved());
+        assert!(!parse("Ctrl+0").is_reserved());
+        assert!(!parse("Ctrl+Shift+3").is_reserved());
+        assert!(!parse("Cmd+Alt+Q").is_reserved());
+    }
+
+    #[test]
+    fn validate_reports_duplicates_and_conflicts() {
+        let (valid, errors) = validate(&bindings(&[
+            (HotkeyAction::ToggleWindows, "Ctrl+Alt+K"),
+            (HotkeyAction::QuickAddTodo, "alt+ctrl+k"),
+            (HotkeyAction::StartTimer, "Cmd+Space"),
+            (HotkeyAction::PreviousDesktop, "Ctrl+"),
+            (HotkeyAction::OpenSwitcher, "  "),
+        ]));
+        assert_eq!(valid, vec![(HotkeyAction::ToggleWindows, parse("Ctrl+Alt+K"))]);
+
+        let messages: Vec<(HotkeyAction, &str)> = errors.iter().map(|e| (e.action, e.message.as_str())).collect();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0], (HotkeyAction::QuickAddTodo, "Ctrl+Alt+K is already used by ToggleWindows"));
+        assert_eq!(messages[1], (HotkeyAction::StartTimer, "Cmd+Space is reserved by macOS"));
+        assert_eq!(messages[2].0, HotkeyAction::PreviousDesktop);
+        // The original text is kept for the UI
+        assert_eq!(errors[0].accelerator, "alt+ctrl+k");
+    }
+
+    #[test]
+    fn validate_is_independent_of_map_order() {
+        // The earlier action in declaration order keeps a shared shortcut
+        let (valid, errors) = validate(&bindings(&[
+            (HotkeyAction::OpenSwitcher, "Ctrl+Alt+S"),
+            (HotkeyAction::StartTimer, "Ctrl+Alt+S"),
+        ]));
+        assert_eq!(valid, vec![(HotkeyAction::StartTimer, parse("Ctrl+Alt+S"))]);
+        assert_eq!(errors[0].action, HotkeyAction::OpenSwitcher);
+    }
+}
@@ -0,0 +1,339 @@
+//! Apple `.icns` icon decoding and PNG rendering.
+//!
+//! Handles PNG-backed entries (`ic07`–`ic14`, `icp4`–`icp6`, used by every
+//! modern app) and the older RLE-compressed RGB entries (`is32`, `il32`,
+//! `ih32`, `it32` with their `*8mk` masks, and `ARGB` `ic04`/`ic05`).
+//! JPEG 2000 entries are skipped.
+
+use std::io::Cursor;
+
+/// Decoded straight RGBA, 8 bits per channel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Pixel size of each icon type we can decode.
+const ICON_TYPES: &[(&[u8; 4], u32)] = &[
+    (b"is32", 16),
+    (b"il32", 32),
+    (b"ih32", 48),
+    (b"it32", 128),
+    (b"ic04", 16),
+    (b"ic05", 32),
+    (b"icp4", 16),
+    (b"icp5", 32),
+    (b"icp6", 64),
+    (b"ic07", 128),
+    (b"ic08", 256),
+    (b"ic09", 512),
+    (b"ic10", 1024),
+    (b"ic11", 32),
+    (b"ic12", 64),
+    (b"ic13", 256),
+    (b"ic14", 512),
+];
+
+/// Alpha mask for each RLE RGB type.
+const MASKS: &[(&[u8; 4], &[u8; 4])] = &[(b"is32", b"s8mk"), (b"il32", b"l8mk"), (b"ih32", b"h8mk"), (b"it32", b"t8mk")];
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// An icon entry's four-character type and its data.
+pub type Entry<'a> = ([u8; 4], &'a [u8]);
+
+/// Split an icns file into its entries.
+pub fn entries(data: &[u8]) -> Result<Vec<Entry<'_>>, String> {
+    if data.len() < 8 || &data[..4] != b"icns" {
+        return Err("Not an icns file".to_string());
+    }
+    let total = (u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize).min(data.len());
+
+    let mut out = Vec::new();
+    let mut offset = 8;
+    while offset + 8 <= total {
+        let ostype: [u8; 4] = data[offset..offset + 4].try_into().unwrap();
+        let len = u32::from_be_bytes(data[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        if len < 8 || offset + len > total {
+            return Err(format!("Corrupt entry '{}'", String::from_utf8_lossy(&ostype)));
+        }
+        out.push((ostype, &data[offset + 8..offset + len]));
+        offset += len;
+    }
+    Ok(out)
+}
+
+/// Unpack one channel of Apple's icon RLE into `out[channel::4]`. A header
+/// byte below 0x80 means "copy the next n+1 bytes"; otherwise the next byte
+/// repeats n-125 times.
+fn unpack_channel(data: &[u8], pos: &mut usize, out: &mut [u8], channel: usize) -> Result<(), String> {
+    let pixels = out.len() / 4;
+    let mut written = 0;
+    while written < pixels {
+        let header = *data.get(*pos).ok_or("Truncated RLE data")? as usize;
+        *pos += 1;
+        if header < 0x80 {
+            let count = header + 1;
+            let bytes = data.get(*pos..*pos + count).ok_or("Truncated RLE data")?;
+            for (i, &b) in bytes.iter().enumerate().take(pixels - written) {
+                out[(written + i) * 4 + channel] = b;
+            }
+            *pos += count;
+            written += count;
+        } else {
+            let count = header - 125;
+            let b = *data.get(*pos).ok_or("Truncated RLE data")?;
+            *pos += 1;
+            for i in 0..count.min(pixels - written) {
+                out[(written + i) * 4 + channel] = b;
+            }
+            written += count;
+        }
+    }
+    Ok(())
+}
+
+fn decode_png(data: &[u8]) -> Result<RgbaImage, String> {
+    let mut decoder = png::Decoder::new(Cursor::new(data));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+    buf.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::Rgb => buf.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => buf.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return Err("Unexpanded indexed PNG".to_string()),
+    };
+    Ok(RgbaImage { width: info.width, height: info.height, pixels })
+}
+
+/// Decode the RLE entry `ostype` (RGB or ARGB), applying its mask if any.
+fn decode_rle(ostype: &[u8; 4], data: &[u8], size: u32, all: &[Entry]) -> Result<RgbaImage, String> {
+    let mut pixels = vec![255u8; (size * size * 4) as usize];
+    let mut pos = 0;
+    if data.starts_with(b"ARGB") {
+        pos = 4;
+        // Stored as A, R, G, B planes.
+        for channel in [3, 0, 1, 2] {
+            unpack_channel(data, &mut pos, &mut pixels, channel)?;
+        }
+    } else {
+        if ostype == b"it32" {
+            pos = 4; // four reserved zero bytes
+        }
+        for channel in 0..3 {
+            unpack_channel(data, &mut pos, &mut pixels, channel)?;
+        }
+        let mask_type = MASKS.iter().find(|(t, _)| *t == ostype).map(|(_, m)| *m);
+        let mask = mask_type.and_then(|m| all.iter().find(|(t, _)| t == m)).map(|(_, d)| *d);
+        if let Some(mask) = mask.filter(|m| m.len() >= (size * size) as usize) {
+            for (i, &a) in mask.iter().take((size * size) as usize).enumerate() {
+                pixels[i * 4 + 3] = a;
+            }
+        }
+    }
+    Ok(RgbaImage { width: size, height: size, pixels })
+}
+
+/// Decode the entry best suited to `size`: the smallest one at least that
+/// big, else the biggest. Entries that fail to decode are skipped.
+pub fn decode_best(data: &[u8], size: u32) -> Result<RgbaImage, String> {
+    let all = entries(data)?;
+    let mut candidates: Vec<(u32, &[u8; 4], &[u8])> = all
+        .iter()
+        .filter_map(|(t, d)| ICON_TYPES.iter().find(|(known, _)| *known == t).map(|&(known, px)| (px, known, *d)))
+        .collect();
+    // Preferred first: big enough and smallest, then the rest largest first.
+    candidates.sort_by_key(|&(px, _, _)| if px >= size { (0, px as i64) } else { (1, -(px as i64)) });
+
+    for (px, ostype, entry) in candidates {
+        let decoded = if entry.starts_with(PNG_SIGNATURE) {
+            decode_png(entry)
+        } else if matches!(ostype, b"is32" | b"il32" | b"ih32" | b"it32" | b"ic04" | b"ic05") {
+            decode_rle(ostype, entry, px, &all)
+        } else {
+            continue; // JPEG 2000
+        };
+        match decoded {
+            Ok(image) => return Ok(image),
+            Err(e) => log::debug!("[icns] skipping {}: {}", String::from_utf8_lossy(ostype), e),
+        }
+    }
+    Err("No decodable icon".to_string())
+}
+
+/// Resize to `size`×`size` (icons are square): area-average when shrinking,
+/// nearest neighbour when growing.
+pub fn resize(image: &RgbaImage, size: u32) -> RgbaImage {
+    if image.width == size && image.height == size {
+        return image.clone();
+    }
+    let (w, h) = (image.width as usize, image.height as usize);
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size as usize {
+        let y0 = y * h / size as usize;
+        let y1 = ((y + 1) * h / size as usize).max(y0 + 1);
+        for x in 0..size as usize {
+            let x0 = x * w / size as usize;
+            let x1 = ((x + 1) * w / size as usize).max(x0 + 1);
+            // Premultiply so transparent pixels don't darken the edges.
+            let mut sum = [0u64; 4];
+            for sy in y0..y1 {
+                for sx in x0..x1 {
+                    let p = &image.pixels[(sy * w + sx) * 4..][..4];
+                    let a = p[3] as u64;
+                    sum[0] += p[0] as u64 * a;
+                    sum[1] += p[1] as u64 * a;
+                    sum[2] += p[2] as u64 * a;
+                    sum[3] += a;
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as u64;
+            let alpha = sum[3];
+            let channel = |c: u64| c.checked_div(alpha).unwrap_or(0) as u8;
+            pixels.extend([channel(sum[0]), channel(sum[1]), channel(sum[2]), (alpha / count) as u8]);
+        }
+    }
+    RgbaImage { width: size, height: size, pixels }
+}
+
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&image.pixels).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+/// Render an icns file as a `size`×`size` PNG.
+pub fn render_png(icns: &[u8], size: u32) -> Result<Vec<u8>, String> {
+    encode_png(&resize(&decode_best(icns, size)?, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/icns").join(name);
+        std::fs::read(path).unwrap()
+    }
+
+    fn pixel(image: &RgbaImage, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * image.width + x) * 4) as usize;
+        image.pixels[i..i + 4].try_into().unwrap()
+    }
+
+    fn solid(size: u32, rgba: [u8; 4]) -> RgbaImage {
+        RgbaImage { width: size, height: size, pixels: rgba.repeat((size * size) as usize) }
+    }
+
+    #[test]
+    fn splits_entries() {
+        let data = fixture("png.icns");
+        let types: Vec<[u8; 4]> = entries(&data).unwrap().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(types, [*b"icp4", *b"ic11", *b"ic07"]);
+        assert!(entries(b"nope").is_err());
+    }
+
+    #[test]
+    fn decode_best_picks_smallest_big_enough() {
+        let data = fixture("png.icns");
+        // Entries: 16 green, 32 red, 128 blue
+        let pick = |size| {
+            let image = decode_best(&data, size).unwrap();
+            (image.width, pixel(&image, 0, 0))
+        };
+        assert_eq!(pick(16), (16, [0, 255, 0, 255]));
+        assert_eq!(pick(20), (32, [255, 0, 0, 255]));
+        assert_eq!(pick(64), (128, [0, 0, 255, 255]));
+        // Nothing big enough: the largest
+        assert_eq!(pick(512), (128, [0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn decode_best_skips_broken_entries() {
+        let mut data = fixture("png.icns");
+        // Append a corrupt 256px PNG entry and fix up the file length
+        let broken = [PNG_SIGNATURE, b"garbage"].concat();
+        data.extend_from_slice(b"ic08");
+        data.extend_from_slice(&(broken.len() as u32 + 8).to_be_bytes());
+        data.extend_from_slice(&broken);
+        let total = data.len() as u32;
+        data[4..8].copy_from_slice(&total.to_be_bytes());
+
+        let image = decode_best(&data, 256).unwrap();
+        assert_eq!((image.width, pixel(&image, 0, 0)), (128, [0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn decodes_rle_with_mask() {
+        let image = decode_best(&fixture("rle.icns"), 16).unwrap();
+        assert_eq!((image.width, image.height), (16, 16));
+        // Red is a repeat run, green literal runs, blue per-row repeats
+        assert_eq!(pixel(&image, 0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 5, 3), [255, 80, 48, 255]);
+        // The s8mk mask makes the right half transparent
+        assert_eq!(pixel(&image, 8, 3)[3], 0);
+        assert_eq!(pixel(&image, 15, 15), [255, 240, 240, 0]);
+    }
+
+    #[test]
+    fn decodes_it32_after_reserved_bytes() {
+        let image = decode_best(&fixture("it32.icns"), 128).unwrap();
+        assert_eq!((image.width, image.height), (128, 128));
+        assert_eq!(pixel(&image, 0, 0), [10, 20, 30, 200]);
+        assert_eq!(pixel(&image, 127, 127), [10, 20, 30, 200]);
+    }
+
+    #[test]
+    fn truncated_rle_is_an_error() {
+        let data = fixture("rle.icns");
+        let all = entries(&data).unwrap();
+        let (_, is32) = all[0];
+        assert!(decode_rle(b"is32", &is32[..is32.len() / 2], 16, &all).is_err());
+    }
+
+    #[test]
+    fn resize_ignores_color_of_transparent_pixels() {
+        // One opaque red pixel among transparent black ones
+        let mut image = solid(2, [0, 0, 0, 0]);
+        image.pixels[..4].copy_from_slice(&[255, 0, 0, 255]);
+        let small = resize(&image, 1);
+        assert_eq!(small.pixels, [255, 0, 0, 63]);
+
+        // Fully transparent stays transparent
+        assert_eq!(resize(&solid(4, [9, 9, 9, 0]), 2).pixels, [0, 0, 0, 0].repeat(4));
+    }
+
+    #[test]
+    fn resize_averages_and_grows() {
+        let mut image = solid(2, [0, 0, 0, 255]);
+        image.pixels[..4].copy_from_slice(&[200, 100, 40, 255]);
+        assert_eq!(resize(&image, 1).pixels, [50, 25, 10, 255]);
+
+        // Growing is nearest neighbour
+        let big = resize(&image, 4);
+        assert_eq!(pixel(&big, 1, 1), [200, 100, 40, 255]);
+        assert_eq!(pixel(&big, 2, 2), [0, 0, 0, 255]);
+        // Same size is a copy
+        assert_eq!(resize(&image, 2), image);
+    }
+
+    #[test]
+    fn renders_png_at_requested_size() {
+        let out = render_png(&fixture("it32.icns"), 32).unwrap();
+        let decoded = decode_png(&out).unwrap();
+        assert_eq!((decoded.width, decoded.height), (32, 32));
+        assert_eq!(pixel(&decoded, 16, 16), [10, 20, 30, 200]);
+    }
+}
@@ -0,0 +1,418 @@
+//! Custom launch commands for common apps.
+//!
+//! A command is an argv template such as `code --new-window {path}`. It is
+//! split into arguments with shell-like quoting, then each argument has its
+//! placeholders substituted, so a value containing spaces or quotes stays a
+//! single argument and is never interpreted by a shell. Running the command
+//! through `sh -c` is a separate, explicit opt-in; placeholders then become
+//! references to positional parameters, so their values never reach the
+//! shell's parser.
+//!
+//! Every launch produces a `LaunchReport` with the exit status and stderr,
+//! since `open` and custom commands fail by exiting non-zero, not by
+//! failing to spawn.
+
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// Values available to a launch template.
+pub struct LaunchContext<'a> {
+    pub path: &'a str,
+    pub app_name: &'a str,
+    pub desktop_title: &'a str,
+}
+
+/// Characters that only mean something to a shell. Commands containing them
+/// were written for `sh -c`.
+/// `#` is handled separately, since it only starts a comment at the start
+/// of a word.
+const SHELL_SYNTAX: &[char] = &['|', '&', ';', '<', '>', '(', ')', '$', '`', '*', '?', '~', '!', '\n'];
+
+/// Placeholders in the order they are passed to `sh -c` as `$1`, `$2`, ...
+const PLACEHOLDERS: &[&str] = &["path", "app_name", "desktop_title"];
+
+/// Split `command` into arguments. Whitespace separates arguments; single
+/// quotes are literal, double quotes allow `\"` and `\\`, and a backslash
+/// outside quotes escapes the next character.
+pub fn split_args(command: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_arg = true;
+                current.push(chars.next().ok_or("Trailing backslash")?);
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Read a placeholder name after its opening `{`, returning its index in
+/// `PLACEHOLDERS`.
+fn placeholder(chars: &mut impl Iterator<Item = char>) -> Result<usize, String> {
+    let mut name = String::new();
+    loop {
+        match chars.next() {
+            Some('}') => break,
+            Some(c) => name.push(c),
+            None => return Err("Unmatched '{'".to_string()),
+        }
+    }
+    PLACEHOLDERS
+        .iter()
+        .position(|p| *p == name)
+        .ok_or_else(|| format!("Unknown placeholder '{{{}}}'", name))
+}
+
+/// Substitute `{placeholder}`s in an argument. `{{` and `}}` produce literal
+/// braces.
+fn expand(template: &str, ctx: &LaunchContext) -> Result<String, String> {
+    let values = [ctx.path, ctx.app_name, ctx.desktop_title];
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => out.push_str(values[placeholder(&mut chars)?]),
+            '}' => return Err("Unmatched '}'".to_string()),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// Rewrite the placeholders in a `sh -c` script as positional parameters:
+/// `"$1"` outside quotes and `${1}` inside double quotes. Inside single
+/// quotes a parameter wouldn't expand, so a placeholder there is an error.
+fn shell_script(template: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut quote = None;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (_, '{') if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            (_, '}') if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            (Some('\''), '{') => {
+                return Err("Placeholders can't be used inside single quotes; write '{{' for a literal brace".to_string())
+            }
+            (Some(_), '{') => out.push_str(&format!("${{{}}}", placeholder(&mut chars)? + 1)),
+            (None, '{') => out.push_str(&format!("\"${}\"", placeholder(&mut chars)? + 1)),
+            (_, '}') => return Err("Unmatched '}'".to_string()),
+            (None | Some('"'), '\\') => {
+                out.push(c);
+                out.extend(chars.next());
+            }
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                out.push(c);
+            }
+            (Some(q), c) if c == q => {
+                quote = None;
+                out.push(c);
+            }
+            (_, c) => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// Build the argv to run for `command`.
+pub fn build_argv(command: &str, raw_shell: bool, ctx: &LaunchContext) -> Result<Vec<String>, String> {
+    if raw_shell {
+        let script = shell_script(command)?;
+        if script.trim().is_empty() {
+            return Err("Empty command".to_string());
+        }
+        // `sh -c script name args...` sets `$0` to name and `$1`... to args
+        let mut argv = vec!["sh".to_string(), "-c".to_string(), script, "sh".to_string()];
+        argv.extend([ctx.path, ctx.app_name, ctx.desktop_title].map(str::to_string));
+        return Ok(argv);
+    }
+    let argv = split_args(command)?
+        .iter()
+        .map(|arg| expand(arg, ctx))
+        .collect::<Result<Vec<_>, _>>()?;
+    if argv.is_empty() {
+        return Err("Empty command".to_string());
+    }
+    Ok(argv)
+}
+
+/// Check `command` without running it. Returns the argv it would run with
+/// placeholders left in place. Shell syntax is rejected unless `raw_shell`
+/// is set, since as argv it would be passed through literally.
+pub fn validate(command: &str, raw_shell: bool) -> Result<Vec<String>, String> {
+    if !raw_shell {
+        if let Some(c) = unquoted_shell_syntax(command) {
+            return Err(format!("'{}' is shell syntax; turn on \"Run through shell\" to use it", c.escape_default()));
+        }
+    }
+    let ctx = LaunchContext { path: "{path}", app_name: "{app_name}", desktop_title: "{desktop_title}" };
+    build_argv(command, raw_shell, &ctx)
+}
+
+/// The first shell-only character outside quotes and escapes, if any.
+fn unquoted_shell_syntax(command: &str) -> Option<char> {
+    let mut quote = None;
+    let mut word_start = true;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, '\\') | (Some('"'), '\\') => {
+                chars.next();
+            }
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if word_start => return Some(c),
+            (None, c) if SHELL_SYNTAX.contains(&c) => return Some(c),
+            _ => {}
+        }
+        word_start = quote.is_none() && c.is_whitespace();
+    }
+    None
+}
+
+/// Whether `command` relies on shell syntax and so only works with `sh -c`.
+pub fn needs_shell(command: &str) -> bool {
+    unquoted_shell_syntax(command).is_some()
+}
+
+/// Quote `s` as an AppleScript string literal.
+pub fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', r"\\").replace('"', "\\\""))
+}
+
+/// How an app was launched.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchStrategy {
+    /// `open`, activating a running instance.
+    Open,
+    /// `open -n`, a new instance.
+    OpenNew,
+    /// A custom launch template run as argv.
+    Command,
+    /// A custom launch command run through `sh -c`.
+    Shell,
+    /// AppleScript that activates the app and sends Cmd+N.
+    NewWindowScript,
+}
+
+/// Keep at most this much of a command's stderr, from the end.
+const MAX_STDERR_CHARS: usize = 2000;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LaunchReport {
+    pub path: String,
+    pub strategy: LaunchStrategy,
+    pub argv: Vec<String>,
+    pub success: bool,
+    /// `None` when the process couldn't be spawned or was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+    pub duration_ms: u64,
+    /// RFC 3339.
+    pub started_at: String,
+    /// The attempt that failed before this strategy was tried.
+    pub fallback_from: Option<Box<LaunchReport>>,
+}
+
+impl LaunchReport {
+    /// A one-line reason for a failed launch.
+    pub fn error(&self) -> Option<String> {
+        if self.success {
+            return None;
+        }
+        let last_line = self.stderr.lines().rev().find(|l| !l.trim().is_empty());
+        Some(match (last_line, self.exit_code) {
+            (Some(line), _) => line.trim().to_string(),
+            (None, Some(code)) => format!("{} exited with code {}", self.argv[0], code),
+            (None, None) => format!("{} was terminated", self.argv[0]),
+        })
+    }
+}
+
+/// Lossy UTF-8, trimmed, keeping the last `MAX_STDERR_CHARS` characters
+/// since the cause of a failure is usually printed last.
+pub fn trim_output(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim();
+    let count = text.chars().count();
+    if count <= MAX_STDERR_CHARS {
+        return text.to_string();
+    }
+    let tail: String = text.chars().skip(count - MAX_STDERR_CHARS).collect();
+    format!("…{}", tail)
+}
+
+/// Run `argv` to completion and report how it went.
+pub fn run(path: &str, strategy: LaunchStrategy, argv: Vec<String>) -> LaunchReport {
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let start = Instant::now();
+    let output = std::process::Command::new(&argv[0]).args(&argv[1..]).output();
+    let duration_ms = start.elapsed().as_millis() as u64;
+    let (success, exit_code, stderr) = match output {
+        Ok(output) => (output.status.success(), output.status.code(), trim_output(&output.stderr)),
+        Err(e) => (false, None, format!("Failed to run {}: {}", argv[0], e)),
+    };
+    LaunchReport {
+        path: path.to_string(),
+        strategy,
+        argv,
+        success,
+        exit_code,
+        stderr,
+        duration_ms,
+        started_at,
+        fallback_from: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_syntax_needs_raw_shell() {
+        assert!(validate("code --new-window {path} | cat", false).unwrap_err().contains("'|'"));
+        assert!(validate("open ~/Desktop", false).is_err());
+        assert_eq!(validate("code --new-window {path} | cat", true).unwrap()[..2], ["sh", "-c"]);
+    }
+
+    #[test]
+    fn quoted_or_escaped_syntax_is_literal() {
+        assert_eq!(validate("open 'https://example.com/?q=a&b'", false).unwrap(), ["open", "https://example.com/?q=a&b"]);
+        assert_eq!(validate(r#"echo "a|b" c\;d"#, false).unwrap(), ["echo", "a|b", "c;d"]);
+        assert!(!needs_shell(r#"osascript -e 'tell application "Google Chrome" to make new window'"#));
+        assert!(needs_shell("open -a Safari; echo done"));
+    }
+
+    /// Values that would break out of naive quoting.
+    const NASTY: &[&str] = &["two words", "it's", r#"say "hi""#, "$(echo pwned)", "`id`", r"back\slash"];
+
+    fn ctx(desktop_title: &str) -> LaunchContext<'_> {
+        LaunchContext { path: "/Applications/Foo Bar.app", app_name: "Foo Bar", desktop_title }
+    }
+
+    fn run_stdout(argv: &[String]) -> String {
+        let output = std::process::Command::new(&argv[0]).args(&argv[1..]).output().unwrap();
+        assert!(output.status.success(), "{:?}", argv);
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn argv_values_stay_single_arguments() {
+        for value in NASTY {
+            let argv = build_argv("echo --title={desktop_title} {path}", false, &ctx(value)).unwrap();
+            assert_eq!(argv, ["echo", &format!("--title={}", value), "/Applications/Foo Bar.app"]);
+        }
+    }
+
+    #[test]
+    fn shell_values_are_never_parsed() {
+        for template in ["printf %s {desktop_title}", r#"printf %s "[{desktop_title}]""#, "printf %s {desktop_title}{{x}}"] {
+            for value in NASTY {
+                let argv = build_argv(template, true, &ctx(value)).unwrap();
+                let expected = match template {
+                    t if t.contains('[') => format!("[{}]", value),
+                    t if t.contains("{{x}}") => format!("{}{{x}}", value),
+                    _ => value.to_string(),
+                };
+                assert_eq!(run_stdout(&argv), expected, "{} with {}", template, value);
+            }
+        }
+    }
+
+    #[test]
+    fn shell_placeholders_become_parameters() {
+        let argv = build_argv(r#"open {path} --args "{app_name}""#, true, &ctx("Work")).unwrap();
+        assert_eq!(argv, ["sh", "-c", r#"open "$1" --args "${2}""#, "sh", "/Applications/Foo Bar.app", "Foo Bar", "Work"]);
+        assert!(validate("echo '{desktop_title}'", true).unwrap_err().contains("single quotes"));
+        assert!(validate("awk '{{print}}' {path}", true).is_ok());
+    }
+
+    #[test]
+    fn hash_only_starts_a_comment_at_a_word() {
+        assert_eq!(validate("open https://example.com/#frag", false).unwrap(), ["open", "https://example.com/#frag"]);
+        assert!(!needs_shell("open https://example.com/page#section"));
+        assert!(needs_shell("open -a Safari # comment"));
+        assert!(needs_shell("#comment"));
+        assert!(!needs_shell("echo '# not a comment'"));
+    }
+
+    #[test]
+    fn applescript_strings_escape_quotes_and_backslashes() {
+        assert_eq!(applescript_string("Safari"), r#""Safari""#);
+        assert_eq!(applescript_string(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(applescript_string(r"a\b"), r#""a\\b""#);
+        assert_eq!(applescript_string("it's"), r#""it's""#);
+    }
+
+    #[test]
+    fn builtin_recipes_validate() {
+        for recipe in crate::recipes::builtin() {
+            if let crate::recipes::Strategy::Command { command, raw_shell } = &recipe.strategy {
+                assert!(validate(command, *raw_shell).is_ok(), "{}", recipe.name);
+            }
+        }
+    }
+}
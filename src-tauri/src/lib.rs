@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::c_void;
 use std::fs;
 use std::sync::Mutex;
@@ -7,9 +7,31 @@ use serde::{Deserialize, Serialize};
 use tauri::image::Image;
 use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
-use tauri::{Emitter, WebviewUrl, WebviewWindowBuilder};
+use tauri::{Emitter, Listener, WebviewUrl, WebviewWindowBuilder};
 use tauri::Manager;
 
+mod apps;
+mod bookmarks;
+mod completed;
+mod fuzzy;
+mod history;
+mod hotkeys;
+mod icns;
+mod launch;
+mod recipes;
+mod recurrence;
+mod spaces;
+mod switching;
+mod usage;
+
+use bookmarks::{Bookmark, BookmarkKind};
+use completed::CompletedItem;
+use history::NavHistory;
+use hotkeys::{HotkeyAction, HotkeyError};
+use recurrence::Recurrence;
+use spaces::{DesktopRecord, ManagedSpace, SpacePosition, SpaceSlot};
+use usage::{AppUsage, UsageTracker};
+
 // ── Hide macOS traffic lights ─────────────────────────────────
 #[cfg(target_os = "macos")]
 fn hide_traffic_lights(window: &tauri::WebviewWindow) {
@@ -67,6 +89,8 @@ const COLORS: &[&str] = &[
 extern "C" {
     fn CGSMainConnectionID() -> i32;
     fn CGSCopyManagedDisplaySpaces(cid: i32) -> *const c_void;
+    /// id64 of the space with keyboard focus, across all displays.
+    fn CGSGetActiveSpace(cid: i32) -> i64;
     fn CGGetActiveDisplayList(max: u32, displays: *mut u32, count: *mut u32) -> i32;
     fn CGDisplayRegisterReconfigurationCallback(
         callback: extern "C" fn(u32, u32, *mut c_void),
@@ -130,9 +154,19 @@ extern "C" {
     ) -> *const c_void;
     fn CFNumberGetValue(num: *const c_void, the_type: isize, out: *mut c_void) -> bool;
     fn CFRelease(cf: *const c_void);
+    fn CFPropertyListCreateData(
+        alloc: *const c_void,
+        plist: *const c_void,
+        format: isize,
+        options: usize,
+        error: *mut *const c_void,
+    ) -> *const c_void;
+    fn CFDataGetBytePtr(data: *const c_void) -> *const u8;
+    fn CFDataGetLength(data: *const c_void) -> isize;
 }
 
 const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+const CF_PROPERTY_LIST_BINARY_FORMAT: isize = 200;
 const CF_NUMBER_SINT64: isize = 4;
 const CF_NUMBER_SINT32: isize = 3;
 
@@ -215,73 +249,200 @@ fn space_info_for_display(target_display: usize) -> (i64, i32) {
     }
 }
 
-// ── Space enumeration (space_id, display, local_1based) ───
+// ── Space enumeration ─────────────────────────────────────────
 
-fn enumerate_spaces() -> Vec<(i64, usize, u32, bool)> {
-    let mut result = Vec::new();
-    unsafe {
+fn enumerate_spaces() -> Vec<ManagedSpace> {
+    // Round-trip through a binary plist so the extraction in
+    // `spaces::managed_spaces` can be tested against recorded snapshots.
+    let bytes = unsafe {
         let conn = CGSMainConnectionID();
-        if conn == 0 { return result; }
+        if conn == 0 { return Vec::new(); }
 
         let displays = CGSCopyManagedDisplaySpaces(conn);
-        if displays.is_null() { return result; }
+        if displays.is_null() { return Vec::new(); }
 
-        let display_count = CFArrayGetCount(displays) as usize;
-        let key_spaces = cf_str("Spaces");
-        let key_type = cf_str("type");
-        let key_id = cf_str("id64");
+        let data = CFPropertyListCreateData(std::ptr::null(), displays, CF_PROPERTY_LIST_BINARY_FORMAT, 0, std::ptr::null_mut());
+        CFRelease(displays);
+        if data.is_null() { return Vec::new(); }
+        let bytes = std::slice::from_raw_parts(CFDataGetBytePtr(data), CFDataGetLength(data) as usize).to_vec();
+        CFRelease(data);
+        bytes
+    };
+    match plist::Value::from_reader(std::io::Cursor::new(bytes)) {
+        Ok(displays) => spaces::managed_spaces(&displays),
+        Err(e) => {
+            log::error!("[spaces] Failed to read managed display spaces: {}", e);
+            Vec::new()
+        }
+    }
+}
 
-        for d in 0..display_count {
-            let display = CFArrayGetValueAtIndex(displays, d as isize);
-            let spaces = CFDictionaryGetValue(display, key_spaces);
-            if spaces.is_null() { continue; }
-
-            let count = CFArrayGetCount(spaces);
-            let mut local: u32 = 1; // 1-based for Ctrl+N shortcuts
-            for i in 0..count {
-                let space = CFArrayGetValueAtIndex(spaces, i);
-                let type_ptr = CFDictionaryGetValue(space, key_type);
-                let mut stype: i32 = -1;
-                if !type_ptr.is_null() {
-                    CFNumberGetValue(type_ptr, CF_NUMBER_SINT32, &mut stype as *mut _ as *mut c_void);
-                }
+// ── Keyboard simulation (switch space) ─────────────────────────
 
-                let id_ptr = CFDictionaryGetValue(space, key_id);
-                let mut sid: i64 = 0;
-                if !id_ptr.is_null() {
-                    CFNumberGetValue(id_ptr, CF_NUMBER_SINT64, &mut sid as *mut _ as *mut c_void);
-                }
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventCreateKeyboardEvent(source: *const c_void, keycode: u16, key_down: bool) -> *const c_void;
+    fn CGEventSetFlags(event: *const c_void, flags: u64);
+    fn CGEventPost(tap: u32, event: *const c_void);
+}
+
+extern "C" {
+    fn CFPreferencesCopyAppValue(key: *const c_void, application_id: *const c_void) -> *const c_void;
+    fn CFGetTypeID(cf: *const c_void) -> usize;
+    fn CFBooleanGetTypeID() -> usize;
+    fn CFBooleanGetValue(boolean: *const c_void) -> bool;
+}
+
+const CG_HID_EVENT_TAP: u32 = 0;
+const CG_EVENT_FLAG_CONTROL: u64 = 0x0004_0000;
+const CG_EVENT_FLAG_SECONDARY_FN: u64 = 0x0080_0000;
+/// Symbolic hotkey ids for "Move left/right a space" and "Switch to Desktop 1".
+const HOTKEY_SPACE_LEFT: u32 = 79;
+const HOTKEY_SPACE_RIGHT: u32 = 81;
+const HOTKEY_DESKTOP_1: u32 = 118;
+/// Pause between Ctrl+Arrow presses so each animation registers.
+const STEP_DELAY_MS: u64 = 250;
+/// How long to wait for the active space to change after a switch.
+const SWITCH_CONFIRM_TIMEOUT_MS: u64 = 1500;
+
+/// Look up an enabled Mission Control shortcut in `com.apple.symbolichotkeys`
+/// and return its (keycode, modifier flags). Returns `None` when the shortcut
+/// is missing or turned off.
+fn symbolic_hotkey(id: u32) -> Option<(u16, u64)> {
+    unsafe {
+        let key_hotkeys = cf_str("AppleSymbolicHotKeys");
+        let domain = cf_str("com.apple.symbolichotkeys");
+        let hotkeys = CFPreferencesCopyAppValue(key_hotkeys, domain);
+        CFRelease(key_hotkeys);
+        CFRelease(domain);
+        if hotkeys.is_null() {
+            return None;
+        }
 
-                let is_fullscreen = stype != 0;
-                result.push((sid, d, local, is_fullscreen));
-                local += 1;
+        let key_id = cf_str(&id.to_string());
+        let key_enabled = cf_str("enabled");
+        let key_value = cf_str("value");
+        let key_parameters = cf_str("parameters");
+
+        let mut result = None;
+        let entry = CFDictionaryGetValue(hotkeys, key_id);
+        if !entry.is_null() {
+            let enabled_ptr = CFDictionaryGetValue(entry, key_enabled);
+            let enabled = if enabled_ptr.is_null() {
+                false
+            } else if CFGetTypeID(enabled_ptr) == CFBooleanGetTypeID() {
+                CFBooleanGetValue(enabled_ptr)
+            } else {
+                let mut n: i32 = 0;
+                CFNumberGetValue(enabled_ptr, CF_NUMBER_SINT32, &mut n as *mut _ as *mut c_void) && n != 0
+            };
+
+            // parameters = (ascii char, virtual keycode, modifier flags)
+            let value = CFDictionaryGetValue(entry, key_value);
+            let params = if value.is_null() { value } else { CFDictionaryGetValue(value, key_parameters) };
+            if enabled && !params.is_null() && CFArrayGetCount(params) >= 3 {
+                let mut keycode: i64 = 0;
+                let mut flags: i64 = 0;
+                if CFNumberGetValue(CFArrayGetValueAtIndex(params, 1), CF_NUMBER_SINT64, &mut keycode as *mut _ as *mut c_void)
+                    && CFNumberGetValue(CFArrayGetValueAtIndex(params, 2), CF_NUMBER_SINT64, &mut flags as *mut _ as *mut c_void)
+                {
+                    result = Some((keycode as u16, flags as u64));
+                }
             }
         }
 
-        CFRelease(key_spaces);
-        CFRelease(key_type);
         CFRelease(key_id);
-        CFRelease(displays);
+        CFRelease(key_enabled);
+        CFRelease(key_value);
+        CFRelease(key_parameters);
+        CFRelease(hotkeys);
+        result
     }
-    result
 }
 
-// ── Keyboard simulation (Ctrl+Arrow to switch space) ──────────
+/// The shortcut for "Switch to Desktop N", if the user has it enabled.
+fn direct_desktop_shortcut(number: u32) -> Option<(u16, u64)> {
+    symbolic_hotkey(HOTKEY_DESKTOP_1 + number - 1)
+}
 
-fn simulate_desktop_switch(steps: i32) {
-    // key code 123 = left arrow, 124 = right arrow
-    let keycode = if steps > 0 { 124 } else { 123 };
-    for _ in 0..steps.unsigned_abs() {
-        let script = format!(
-            "tell application \"System Events\" to key code {} using control down",
-            keycode
-        );
-        std::process::Command::new("osascript")
-            .arg("-e")
-            .arg(&script)
-            .output()
-            .ok();
-        std::thread::sleep(std::time::Duration::from_millis(300));
+/// Post a key press straight into the HID event stream.
+fn post_key(keycode: u16, flags: u64) {
+    unsafe {
+        for key_down in [true, false] {
+            let event = CGEventCreateKeyboardEvent(std::ptr::null(), keycode, key_down);
+            if event.is_null() {
+                continue;
+            }
+            CGEventSetFlags(event, flags);
+            CGEventPost(CG_HID_EVENT_TAP, event);
+            CFRelease(event);
+        }
+    }
+}
+
+fn simulate_desktop_switch(plan: switching::SwitchPlan) {
+    match plan {
+        switching::SwitchPlan::Direct(number) => {
+            if let Some((keycode, flags)) = direct_desktop_shortcut(number) {
+                post_key(keycode, flags);
+            }
+        }
+        switching::SwitchPlan::Step(steps) => {
+            // Defaults: Ctrl+Left (123) / Ctrl+Right (124)
+            let (hotkey, default_keycode) = if steps > 0 { (HOTKEY_SPACE_RIGHT, 124) } else { (HOTKEY_SPACE_LEFT, 123) };
+            let (keycode, flags) = symbolic_hotkey(hotkey)
+                .unwrap_or((default_keycode, CG_EVENT_FLAG_CONTROL | CG_EVENT_FLAG_SECONDARY_FN));
+            for i in 0..steps.unsigned_abs() {
+                if i > 0 {
+                    std::thread::sleep(std::time::Duration::from_millis(STEP_DELAY_MS));
+                }
+                post_key(keycode, flags);
+            }
+        }
+    }
+}
+
+/// Bumped by the space observer on every active-space change; switches wait
+/// on it to confirm they landed.
+static SPACE_CHANGES: (Mutex<u64>, std::sync::Condvar) = (Mutex::new(0), std::sync::Condvar::new());
+
+fn notify_space_changed() {
+    let (count, changed) = &SPACE_CHANGES;
+    *count.lock().unwrap() += 1;
+    changed.notify_all();
+}
+
+/// The live system: CGS for the active space, CGEvent for keys and the
+/// NSWorkspace observer for change notifications.
+struct SystemSpaces;
+
+impl switching::SpaceSource for SystemSpaces {
+    fn active_space(&self, display: usize) -> i64 {
+        space_info_for_display(display).0
+    }
+
+    fn can_post_keys(&self) -> bool {
+        unsafe { AXIsProcessTrusted() }
+    }
+
+    fn has_direct_shortcut(&self, number: u32) -> bool {
+        direct_desktop_shortcut(number).is_some()
+    }
+
+    fn post(&self, plan: switching::SwitchPlan) {
+        simulate_desktop_switch(plan);
+    }
+
+    fn change_count(&self) -> u64 {
+        *SPACE_CHANGES.0.lock().unwrap()
+    }
+
+    fn wait_for_change(&self, seen: u64, timeout: std::time::Duration) {
+        // Wake up periodically too, in case a notification was missed.
+        let timeout = timeout.min(std::time::Duration::from_millis(250));
+        let (count, changed) = &SPACE_CHANGES;
+        let guard = count.lock().unwrap();
+        let _ = changed.wait_timeout_while(guard, timeout, |c| *c == seen);
     }
 }
 
@@ -292,6 +453,19 @@ struct TodoItem {
     id: String,
     text: String,
     done: bool,
+    /// Pinned items are carried forward (reset to not done) when a new session starts.
+    #[serde(default)]
+    pinned: bool,
+    /// RRULE-style schedule; completing a recurring todo reschedules it instead of checking it off.
+    #[serde(default)]
+    recurrence: Option<Recurrence>,
+    /// Next occurrence (RFC 3339) of a recurring todo.
+    #[serde(default)]
+    due: Option<String>,
+    /// Local wall-clock time the series started at. Every occurrence is
+    /// resolved from it, so one moved by a DST gap doesn't shift the rest.
+    #[serde(default)]
+    dtstart: Option<chrono::NaiveDateTime>,
 }
 
 type NotesStore = HashMap<i64, Vec<TodoItem>>;
@@ -306,33 +480,125 @@ struct SavedContext {
 
 type ContextHistoryStore = HashMap<i64, Vec<SavedContext>>;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-struct CompletedItem {
-    id: String,
-    text: String,
-    desktop_id: i64,
-    #[serde(default)]
-    desktop_name: Option<String>,
-    completed_at: String,
-}
-
 fn default_desktop_count() -> u32 { 10 }
 fn default_timer_presets() -> Vec<u32> { vec![60, 300, 600] }
 fn default_notify_system() -> bool { true }
 fn default_notify_flash() -> bool { true }
-#[derive(Serialize, Deserialize, Clone, Debug)]
+
+/// Which todos survive a session rollover.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum CarryMode {
+    /// Every unfinished todo and every title.
+    All,
+    /// Only pinned todos and pinned titles.
+    #[default]
+    Pinned,
+    None,
+}
+
+/// What clicking a common app does.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum AppAction {
+    /// Bring the running app forward, launching it if needed.
+    ActivateExisting,
+    /// Open a new window or instance, with `launch_args` if set.
+    #[default]
+    NewWindow,
+    /// Open a new window and move it to the clicked window's monitor.
+    NewWindowOnMonitor,
+}
+
+/// A user-defined heading for common apps, e.g. "Browsers".
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct AppGroup {
+    id: String,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 struct CommonApp {
     name: String,
+    /// Last known location. Launches resolve `bundle_id` first, so this is
+    /// updated when the app moves.
     path: String,
+    /// `CFBundleIdentifier`, which identifies the app across moves and
+    /// reinstalls. `None` for bundles without one.
+    #[serde(default)]
+    bundle_id: Option<String>,
     #[serde(default)]
     short_name: Option<String>,
+    /// Custom launch command: an argv template with `{path}`, `{app_name}`
+    /// and `{desktop_title}` placeholders, run without a shell.
     #[serde(default)]
     launch_args: Option<String>,
+    /// Run `launch_args` through `sh -c` instead. Placeholders are passed
+    /// as positional parameters.
+    #[serde(default)]
+    raw_shell: bool,
+    /// Position among the common apps, across groups. Kept equal to the
+    /// app's index in `Settings::common_apps`.
+    #[serde(default)]
+    order: u32,
+    /// `AppGroup::id`, or `None` for ungrouped.
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    default_action: AppAction,
+}
+
+impl CommonApp {
+    /// Stable identifier for commands: the lowercased bundle id, or the path
+    /// for apps without one.
+    fn key(&self) -> String {
+        self.bundle_id.as_ref().map_or_else(|| self.path.clone(), |id| id.to_lowercase())
+    }
+
+    /// Same app: by bundle id when both have one, otherwise by path.
+    fn same_app(&self, other: &CommonApp) -> bool {
+        match (&self.bundle_id, &other.bundle_id) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            _ => self.path == other.path,
+        }
+    }
+
+    /// Fill in `bundle_id` from the bundle at `path` if it's missing.
+    fn fill_bundle_id(&mut self) {
+        if self.bundle_id.is_none() {
+            self.bundle_id = apps::bundle_id_at(&self.path);
+        }
+    }
+}
+
+/// Drop later entries for an app already in `apps`.
+fn dedupe_common_apps(apps: &mut Vec<CommonApp>) {
+    let mut kept: Vec<CommonApp> = Vec::with_capacity(apps.len());
+    for app in apps.drain(..) {
+        if !kept.iter().any(|k| k.same_app(&app)) {
+            kept.push(app);
+        }
+    }
+    *apps = kept;
+}
+
+/// Make `order` match each app's position and drop groups that no longer
+/// exist.
+fn normalize_common_apps(apps: &mut [CommonApp], groups: &[AppGroup]) {
+    for (i, app) in apps.iter_mut().enumerate() {
+        app.order = i as u32;
+        if app.group.as_ref().is_some_and(|g| !groups.iter().any(|group| &group.id == g)) {
+            app.group = None;
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct Settings {
     custom_colors: HashMap<i64, String>,
+    /// Persistent user-defined desktop names, unlike the per-session title.
+    #[serde(default)]
+    custom_names: HashMap<i64, String>,
     setup_complete: bool,
     #[serde(default = "default_desktop_count")]
     desktop_count: u32,
@@ -344,18 +610,48 @@ struct Settings {
     notify_flash: bool,
     #[serde(default)]
     hidden_panels: Vec<String>,
+    /// In display order; see `CommonApp::order`.
     #[serde(default)]
     common_apps: Vec<CommonApp>,
+    /// Common app groups, in display order.
+    #[serde(default)]
+    app_groups: Vec<AppGroup>,
     #[serde(default)]
     auto_hide_delay: u32,
     #[serde(default)]
     dismissed_tips: Vec<String>,
+    /// Local "HH:MM" at which a new session starts automatically; `None` disables rollover.
+    #[serde(default)]
+    rollover_time: Option<String>,
+    #[serde(default)]
+    rollover_carry: CarryMode,
+    /// Days of completed items kept in the live store; older ones move to
+    /// monthly archive files. `None` keeps everything live.
+    #[serde(default)]
+    completed_retention_days: Option<u32>,
+    /// Leave full-screen app spaces out of desktop numbering and theme colors.
+    #[serde(default)]
+    exclude_fullscreen: bool,
+    /// Full-screen spaces show their parent desktop's todos and title.
+    #[serde(default)]
+    fullscreen_todos_to_parent: bool,
+    /// Global shortcut per action, e.g. "Ctrl+Alt+K". Unbound actions are absent.
+    #[serde(default)]
+    hotkeys: HashMap<HotkeyAction, String>,
+    /// After a session is continued or restored, open each desktop's app set
+    /// the first time it's visited.
+    #[serde(default)]
+    launch_apps_on_restore: bool,
+    /// Bookmarks shown on every desktop.
+    #[serde(default)]
+    bookmarks: Vec<Bookmark>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             custom_colors: HashMap::new(),
+            custom_names: HashMap::new(),
             setup_complete: false,
             desktop_count: 10,
             timer_presets: default_timer_presets(),
@@ -363,8 +659,17 @@ impl Default for Settings {
             notify_flash: true,
             hidden_panels: Vec::new(),
             common_apps: Vec::new(),
+            app_groups: Vec::new(),
             auto_hide_delay: 0,
             dismissed_tips: Vec::new(),
+            rollover_time: None,
+            rollover_carry: CarryMode::default(),
+            completed_retention_days: None,
+            exclude_fullscreen: false,
+            fullscreen_todos_to_parent: false,
+            hotkeys: HashMap::new(),
+            launch_apps_on_restore: false,
+            bookmarks: Vec::new(),
         }
     }
 }
@@ -383,6 +688,25 @@ struct PersistData {
     context_history: ContextHistoryStore,
     #[serde(default)]
     completed: Vec<CompletedItem>,
+    /// Desktops whose title survives `start_new_session`.
+    #[serde(default)]
+    pinned_titles: HashSet<i64>,
+    /// When the last automatic rollover ran (RFC 3339).
+    #[serde(default)]
+    last_rollover: Option<String>,
+    /// Known desktops. Since v2 every per-desktop map is keyed by
+    /// `DesktopRecord::id` rather than the unstable CGS id64.
+    #[serde(default)]
+    desktops: Vec<DesktopRecord>,
+    /// Apps to open on each desktop, keyed by desktop id.
+    #[serde(default)]
+    app_sets: HashMap<i64, Vec<CommonApp>>,
+    /// Time spent per frontmost app on each desktop, keyed by desktop id.
+    #[serde(default)]
+    app_usage: HashMap<i64, Vec<AppUsage>>,
+    /// Bookmarks pinned to one desktop, keyed by desktop id.
+    #[serde(default)]
+    bookmarks: HashMap<i64, Vec<Bookmark>>,
 }
 
 struct AppState {
@@ -419,16 +743,24 @@ fn migrate_v0_to_v1(data: &mut PersistData) {
     let pos_to_sid: HashMap<i64, i64> = spaces
         .iter()
         .enumerate()
-        .map(|(pos, &(sid, _disp, _local, _fs))| (pos as i64, sid))
+        .map(|(pos, space)| (pos as i64, space.space_id))
         .collect();
 
     fn rekey<V: Clone>(old: &HashMap<i64, V>, mapping: &HashMap<i64, i64>) -> HashMap<i64, V> {
         let mut new = HashMap::new();
         for (old_key, value) in old {
-            if let Some(&new_key) = mapping.get(old_key) {
-                new.insert(new_key, value.clone());
+            match mapping.get(old_key) {
+                Some(&new_key) => {
+                    new.insert(new_key, value.clone());
+                }
+                None => {
+                    // Keep entries that don't map to a current space under a
+                    // negative key (positions are >= 0, space ids > 0) so they
+                    // surface as orphans instead of being dropped.
+                    log::warn!("[migrate] no space at position {}; keeping its data as an orphan", old_key);
+                    new.insert(-old_key - 1, value.clone());
+                }
             }
-            // Drop entries that don't map to a current space
         }
         new
     }
@@ -439,40 +771,266 @@ fn migrate_v0_to_v1(data: &mut PersistData) {
     data.version = 1;
 }
 
+/// Every desktop id referenced by per-desktop data.
+fn store_keys(data: &PersistData) -> HashSet<i64> {
+    data.notes
+        .keys()
+        .chain(data.titles.keys())
+        .chain(data.settings.custom_colors.keys())
+        .chain(data.settings.custom_names.keys())
+        .chain(data.context_history.keys())
+        .chain(data.pinned_titles.iter())
+        .chain(data.app_sets.keys())
+        .chain(data.app_usage.keys())
+        .chain(data.bookmarks.keys())
+        .copied()
+        .collect()
+}
+
+/// Migrate v1 data (keyed by space id64) to v2 (keyed by stable desktop id).
+/// The first reconciliation reuses each space's current id64 as its desktop
+/// id, so existing keys carry over unchanged.
+fn migrate_v1_to_v2(data: &mut PersistData) {
+    if data.version >= 2 {
+        return;
+    }
+    let reserved = store_keys(data);
+    spaces::reconcile(&mut data.desktops, &enumerate_spaces(), &reserved);
+    data.version = 2;
+}
+
+/// Migrate v2 data to v3. Custom launch commands used to always run
+/// through `sh -c`; now they're argv templates unless `raw_shell` is set, so
+/// commands that rely on shell syntax keep the shell.
+fn migrate_v2_to_v3(data: &mut PersistData) {
+    if data.version >= 3 {
+        return;
+    }
+    let apps = data.settings.common_apps.iter_mut().chain(data.app_sets.values_mut().flatten());
+    for app in apps {
+        if let Some(command) = &app.launch_args {
+            app.raw_shell = launch::needs_shell(command) || launch::split_args(command).is_err();
+        }
+    }
+    data.version = 3;
+}
+
+/// Migrate v3 data to v4. Common apps and app sets were keyed by path; now
+/// they carry the bundle id read from each app's `Info.plist`. Apps that
+/// can't be read any more keep `None` and show up in the health check.
+fn migrate_v3_to_v4(data: &mut PersistData) {
+    if data.version >= 4 {
+        return;
+    }
+    let apps = data.settings.common_apps.iter_mut().chain(data.app_sets.values_mut().flatten());
+    for app in apps {
+        app.fill_bundle_id();
+    }
+    dedupe_common_apps(&mut data.settings.common_apps);
+    for set in data.app_sets.values_mut() {
+        dedupe_common_apps(set);
+    }
+    data.version = 4;
+}
+
+/// Migrate v4 data to v5. Common apps gain an explicit `order`, taken from
+/// their current position, so the existing list keeps its order. Groups and
+/// the default action start out empty and as "new window", which is what
+/// clicking an app did before.
+fn migrate_v4_to_v5(data: &mut PersistData) {
+    if data.version >= 5 {
+        return;
+    }
+    normalize_common_apps(&mut data.settings.common_apps, &data.settings.app_groups);
+    for set in data.app_sets.values_mut() {
+        normalize_common_apps(set, &[]);
+    }
+    data.version = 5;
+}
+
+/// The app that owns a full-screen space.
+#[derive(Serialize, Clone, Debug)]
+struct SpaceOwner {
+    name: String,
+    bundle_id: Option<String>,
+}
+
+/// Owners of current full-screen spaces as `(space id64, pid, owner)`, so
+/// enumerating desktops (every topology poll) doesn't ask AppKit each time.
+static SPACE_OWNERS: Mutex<Vec<(i64, i32, Option<SpaceOwner>)>> = Mutex::new(Vec::new());
+
+/// Owners for `spaces`, from the cache where the space and pid still match.
+/// Entries for spaces that are gone are dropped.
+fn space_owners(spaces: &[ManagedSpace]) -> Vec<Option<SpaceOwner>> {
+    let mut cache = SPACE_OWNERS.lock().unwrap();
+    cache.retain(|(sid, pid, _)| spaces.iter().any(|s| s.space_id == *sid && s.owner_pid == Some(*pid)));
+    spaces
+        .iter()
+        .map(|space| {
+            let pid = space.owner_pid?;
+            if let Some((_, _, owner)) = cache.iter().find(|(sid, _, _)| *sid == space.space_id) {
+                return owner.clone();
+            }
+            let owner = space_owner(pid);
+            cache.push((space.space_id, pid, owner.clone()));
+            owner
+        })
+        .collect()
+}
+
+fn space_owner(pid: i32) -> Option<SpaceOwner> {
+    use objc2_app_kit::NSRunningApplication;
+    let app = NSRunningApplication::runningApplicationWithProcessIdentifier(pid)?;
+    Some(SpaceOwner {
+        name: app.localizedName()?.to_string(),
+        bundle_id: app.bundleIdentifier().map(|b| b.to_string()),
+    })
+}
+
+/// A current space paired with its stable desktop id.
+struct Desktop {
+    id: i64,
+    space: ManagedSpace,
+    slot: SpaceSlot,
+    /// Stable id of the desktop a full-screen space belongs to.
+    parent_id: Option<i64>,
+    owner: Option<SpaceOwner>,
+}
+
+impl Desktop {
+    /// Custom name, else the full-screen app's name, else the per-display number.
+    fn name(&self, settings: &Settings) -> String {
+        if let Some(name) = settings.custom_names.get(&self.id) {
+            return name.clone();
+        }
+        if self.space.is_fullscreen {
+            return self.owner.as_ref().map_or_else(|| "Full Screen".to_string(), |o| o.name.clone());
+        }
+        format!("Desktop {}", self.slot.local)
+    }
+
+    fn color(&self, settings: &Settings) -> String {
+        let key = match self.parent_id {
+            Some(parent) if settings.exclude_fullscreen => parent,
+            _ => self.id,
+        };
+        settings.custom_colors.get(&key)
+            .cloned()
+            .unwrap_or_else(|| default_color(self.slot.position))
+    }
+
+    /// The desktop whose todos and title this space shows.
+    fn data_id(&self, settings: &Settings) -> i64 {
+        match self.parent_id {
+            Some(parent) if settings.fullscreen_todos_to_parent => parent,
+            _ => self.id,
+        }
+    }
+}
+
+/// Enumerate spaces and resolve each to its stable desktop id, registering
+/// new spaces and re-linking ones whose id64 changed.
+fn current_desktops(state: &AppState) -> Vec<Desktop> {
+    let spaces = enumerate_spaces();
+    let mut data = state.data.lock().unwrap();
+    let reserved = store_keys(&data);
+    let (ids, changed) = spaces::reconcile(&mut data.desktops, &spaces, &reserved);
+    if changed {
+        let path = state.data_path.lock().unwrap();
+        persist_data(&path, &data);
+    }
+    let slots = spaces::layout_spaces(&spaces, data.settings.exclude_fullscreen);
+    drop(data);
+    let owners = space_owners(&spaces);
+
+    spaces
+        .into_iter()
+        .zip(slots)
+        .zip(owners)
+        .enumerate()
+        .map(|(i, ((space, slot), owner))| Desktop {
+            id: ids[i],
+            parent_id: slot.parent.map(|p| ids[p]),
+            owner,
+            space,
+            slot,
+        })
+        .collect()
+}
+
 // ── Tauri commands ────────────────────────────────────────────
 
 #[derive(Serialize, Clone)]
 struct DesktopInfo {
+    /// Stable desktop id (the key for per-desktop data), not the CGS id64.
     space_id: i64,
     position: u32,
+    display_index: usize,
+    /// 1-based number within its display, as Mission Control shows it.
+    local: u32,
     name: String,
+    custom_name: Option<String>,
     color: String,
     is_fullscreen: bool,
+    /// For full-screen spaces, the app that owns it.
+    owner: Option<SpaceOwner>,
+    parent_id: Option<i64>,
 }
 
 fn default_color(position: u32) -> String {
     COLORS[(position as usize) % COLORS.len()].to_string()
 }
 
+/// Build the `DesktopInfo` for the space with CGS id `sid`. Its `space_id`
+/// is the desktop whose todos should be shown, which for a full-screen space
+/// may be its parent.
+///
+/// A space missing from `desktops` (it appeared after they were enumerated)
+/// falls back to the last known record for that id64. `None` if the space
+/// has never been reconciled, so callers never see a raw id64.
+fn desktop_info(settings: &Settings, records: &[DesktopRecord], desktops: &[Desktop], sid: i64) -> Option<DesktopInfo> {
+    let info = match desktops.iter().position(|d| d.space.space_id == sid) {
+        Some(pos) => {
+            let d = &desktops[pos];
+            DesktopInfo {
+                space_id: d.data_id(settings),
+                position: d.slot.position,
+                display_index: d.space.display,
+                local: d.slot.local,
+                name: d.name(settings),
+                custom_name: settings.custom_names.get(&d.id).cloned(),
+                color: d.color(settings),
+                is_fullscreen: d.space.is_fullscreen,
+                owner: d.owner.clone(),
+                parent_id: d.parent_id,
+            }
+        }
+        None => {
+            let record = records.iter().find(|r| r.space_id == sid)?;
+            let custom_name = settings.custom_names.get(&record.id).cloned();
+            DesktopInfo {
+                space_id: record.id,
+                position: record.local.saturating_sub(1),
+                display_index: record.display,
+                local: record.local,
+                name: custom_name.clone().unwrap_or_else(|| format!("Desktop {}", record.local)),
+                custom_name,
+                color: settings.custom_colors.get(&record.id).cloned().unwrap_or_else(|| default_color(record.local.saturating_sub(1))),
+                is_fullscreen: false,
+                owner: None,
+                parent_id: None,
+            }
+        }
+    };
+    Some(info)
+}
+
 #[tauri::command]
-fn get_desktop(state: tauri::State<'_, AppState>, display: u32) -> DesktopInfo {
+fn get_desktop(state: tauri::State<'_, AppState>, display: u32) -> Result<DesktopInfo, String> {
     let (sid, _space_type) = space_info_for_display(display as usize);
-    let spaces = enumerate_spaces();
-    let (position, is_fullscreen) = spaces.iter()
-        .position(|&(s, _, _, _)| s == sid)
-        .map(|pos| (pos as u32, spaces[pos].3))
-        .unwrap_or((0, false));
+    let desktops = current_desktops(&state);
     let data = state.data.lock().unwrap();
-    let color = data.settings.custom_colors.get(&sid)
-        .cloned()
-        .unwrap_or_else(|| default_color(position));
-    DesktopInfo {
-        space_id: sid,
-        position,
-        name: format!("Desktop {}", position + 1),
-        color,
-        is_fullscreen,
-    }
+    desktop_info(&data.settings, &data.desktops, &desktops, sid).ok_or_else(|| format!("Space {} is not a known desktop", sid))
 }
 
 #[tauri::command]
@@ -507,43 +1065,70 @@ fn save_title(state: tauri::State<'_, AppState>, desktop: i64, title: String) {
     persist_data(&path, &data);
 }
 
+#[tauri::command]
+fn get_title_pinned(state: tauri::State<'_, AppState>, desktop: i64) -> bool {
+    let data = state.data.lock().unwrap();
+    data.pinned_titles.contains(&desktop)
+}
+
+#[tauri::command]
+fn save_title_pinned(state: tauri::State<'_, AppState>, desktop: i64, pinned: bool) {
+    let mut data = state.data.lock().unwrap();
+    if pinned {
+        data.pinned_titles.insert(desktop);
+    } else {
+        data.pinned_titles.remove(&desktop);
+    }
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+}
+
 #[derive(Serialize, Clone)]
 struct DesktopSummary {
+    /// Stable desktop id, as in `DesktopInfo`.
     space_id: i64,
     position: u32,
+    display_index: usize,
+    local: u32,
     name: String,
+    custom_name: Option<String>,
     title: String,
     color: String,
     todo_count: usize,
     is_fullscreen: bool,
+    owner: Option<SpaceOwner>,
+    parent_id: Option<i64>,
+}
+
+fn desktop_summary(data: &PersistData, desktop: &Desktop) -> DesktopSummary {
+    let data_id = desktop.data_id(&data.settings);
+    let todos = data.notes.get(&data_id);
+    let active_count = todos
+        .map(|t| t.iter().filter(|i| !i.done).count())
+        .unwrap_or(0);
+    DesktopSummary {
+        space_id: desktop.id,
+        position: desktop.slot.position,
+        display_index: desktop.space.display,
+        local: desktop.slot.local,
+        name: desktop.name(&data.settings),
+        custom_name: data.settings.custom_names.get(&desktop.id).cloned(),
+        title: data.titles.get(&data_id).cloned().unwrap_or_default(),
+        color: desktop.color(&data.settings),
+        todo_count: active_count,
+        is_fullscreen: desktop.space.is_fullscreen,
+        owner: desktop.owner.clone(),
+        parent_id: desktop.parent_id,
+    }
 }
 
 #[tauri::command]
 fn list_all_desktops(state: tauri::State<'_, AppState>) -> Vec<DesktopSummary> {
-    let spaces = enumerate_spaces();
+    let desktops = current_desktops(&state);
     let data = state.data.lock().unwrap();
-    spaces
+    desktops
         .iter()
-        .enumerate()
-        .map(|(pos, &(sid, _disp, _local, is_fullscreen))| {
-            let position = pos as u32;
-            let todos = data.notes.get(&sid);
-            let active_count = todos
-                .map(|t| t.iter().filter(|i| !i.done).count())
-                .unwrap_or(0);
-            let color = data.settings.custom_colors.get(&sid)
-                .cloned()
-                .unwrap_or_else(|| default_color(position));
-            DesktopSummary {
-                space_id: sid,
-                position,
-                name: format!("Desktop {}", position + 1),
-                title: data.titles.get(&sid).cloned().unwrap_or_default(),
-                color,
-                todo_count: active_count,
-                is_fullscreen,
-            }
-        })
+        .map(|desktop| desktop_summary(&data, desktop))
         .collect()
 }
 
@@ -555,32 +1140,12 @@ struct DisplayGroup {
 
 #[tauri::command]
 fn list_desktops_grouped(state: tauri::State<'_, AppState>) -> Vec<DisplayGroup> {
-    let spaces = enumerate_spaces();
+    let desktops = current_desktops(&state);
     let data = state.data.lock().unwrap();
 
     let mut groups: std::collections::BTreeMap<usize, Vec<DesktopSummary>> = std::collections::BTreeMap::new();
-    let mut global_pos: u32 = 0;
-
-    for &(sid, disp, _local, is_fullscreen) in &spaces {
-        let position = global_pos;
-        let todos = data.notes.get(&sid);
-        let active_count = todos
-            .map(|t| t.iter().filter(|i| !i.done).count())
-            .unwrap_or(0);
-        let color = data.settings.custom_colors.get(&sid)
-            .cloned()
-            .unwrap_or_else(|| default_color(position));
-        let summary = DesktopSummary {
-            space_id: sid,
-            position,
-            name: format!("Desktop {}", position + 1),
-            title: data.titles.get(&sid).cloned().unwrap_or_default(),
-            color,
-            todo_count: active_count,
-            is_fullscreen,
-        };
-        groups.entry(disp).or_default().push(summary);
-        global_pos += 1;
+    for desktop in &desktops {
+        groups.entry(desktop.space.display).or_default().push(desktop_summary(&data, desktop));
     }
 
     groups.into_iter().map(|(display_index, desktops)| {
@@ -588,66 +1153,419 @@ fn list_desktops_grouped(state: tauri::State<'_, AppState>) -> Vec<DisplayGroup>
     }).collect()
 }
 
-#[tauri::command]
-fn switch_desktop(display: u32, target: i64) -> bool {
-    let (current_sid, _) = space_info_for_display(display as usize);
-    let spaces = enumerate_spaces();
-
-    // Find positions of current and target on the same display
-    let display_spaces: Vec<i64> = spaces.iter()
-        .filter(|&&(_, disp, _, _)| disp == display as usize)
-        .map(|&(sid, _, _, _)| sid)
+/// Switch `display` to the desktop with stable id `target` and confirm the
+/// active space changed. Doesn't touch navigation history.
+fn switch_to_desktop(state: &AppState, display: usize, target: i64) -> switching::SwitchResult {
+    let desktops = current_desktops(state);
+    let display_spaces: Vec<ManagedSpace> = desktops.iter()
+        .filter(|d| d.space.display == display)
+        .map(|d| d.space.clone())
         .collect();
+    let target_sid = desktops.iter().find(|d| d.id == target).map(|d| d.space.space_id);
+
+    let result = switching::switch_to(
+        &SystemSpaces,
+        display,
+        &display_spaces,
+        target_sid,
+        std::time::Duration::from_millis(SWITCH_CONFIRM_TIMEOUT_MS),
+    );
+    log::info!("[switch] display {} → desktop {}: {:?}", display, target, result);
+    result
+}
 
-    let current_pos = display_spaces.iter().position(|&s| s == current_sid);
-    let target_pos = display_spaces.iter().position(|&s| s == target);
-
-    match (current_pos, target_pos) {
-        (Some(cur), Some(tgt)) if cur != tgt => {
-            let steps = tgt as i32 - cur as i32;
-            simulate_desktop_switch(steps);
-            true
+/// Switch and record the target as a new visit in the display's history.
+fn perform_switch(state: &AppState, display: usize, target: i64) -> switching::SwitchResult {
+    let result = with_navigation_paused(display, || switch_to_desktop(state, display, target));
+    if result == switching::SwitchResult::Switched {
+        if with_nav(display, |h| h.visit(target)) {
+            emit_navigation_changed(&state.app_handle);
         }
-        _ => false,
+        launch_pending_apps(state, target, display);
     }
+    result
 }
 
-// ── Session commands ───────────────────────────────────────────
+/// `target` is a stable desktop id, resolved to its current space. Runs off
+/// the main thread since stepping through spaces takes a while.
+#[tauri::command]
+async fn switch_desktop(app: tauri::AppHandle, display: u32, target: i64) -> Result<switching::SwitchResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        perform_switch(&state, display as usize, target)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
 
-const MAX_HISTORY_PER_DESKTOP: usize = 20;
+/// Todo matches rank slightly below name and title matches.
+const TODO_MATCH_PENALTY: i64 = 8;
+const MAX_QUERY_CANDIDATES: usize = 5;
 
-#[tauri::command]
-fn start_new_session(state: tauri::State<'_, AppState>) {
-    let mut data = state.data.lock().unwrap();
-    let now = chrono::Utc::now().to_rfc3339();
+#[derive(Serialize, Clone)]
+struct DesktopMatch {
+    /// Stable desktop id.
+    space_id: i64,
+    display_index: usize,
+    name: String,
+    title: String,
+    /// The name, title or todo text that matched best.
+    matched: String,
+    score: i64,
+}
 
-    // For each desktop with data, push current context into history
-    let space_ids: Vec<i64> = data.notes.keys().chain(data.titles.keys()).copied().collect::<std::collections::HashSet<i64>>().into_iter().collect();
+#[derive(Serialize, Clone)]
+struct QuerySwitch {
+    /// Set when a single desktop matched clearly and a switch was attempted.
+    result: Option<switching::SwitchResult>,
+    /// Ranked matches, best first. More than one without a `result` means
+    /// the query was ambiguous.
+    candidates: Vec<DesktopMatch>,
+}
 
-    for sid in space_ids {
-        let title = data.titles.get(&sid).cloned().unwrap_or_default();
-        let todos = data.notes.get(&sid).cloned().unwrap_or_default();
-        if title.is_empty() && todos.is_empty() {
-            continue;
-        }
-        let entry = SavedContext {
-            title,
-            todos,
-            saved_at: now.clone(),
+/// Rank desktops by how well `query` matches their name, title or open todos.
+fn match_desktops(data: &PersistData, desktops: &[Desktop], query: &str) -> Vec<DesktopMatch> {
+    let mut matches: Vec<DesktopMatch> = desktops
+        .iter()
+        .filter_map(|desktop| {
+            let name = desktop.name(&data.settings);
+            let title = data.titles.get(&desktop.id).cloned().unwrap_or_default();
+            let todos = data.notes.get(&desktop.id).into_iter().flatten().filter(|t| !t.done);
+
+            // Spaces showing their parent's data only match on their own
+            // name, otherwise both would match the same title.
+            let fields = [(name.as_str(), 0), (title.as_str(), 0)]
+                .into_iter()
+                .chain(todos.map(|t| (t.text.as_str(), TODO_MATCH_PENALTY)));
+            let (score, matched) = fields
+                .filter_map(|(text, penalty)| fuzzy::score(query, text).map(|s| (s - penalty, text)))
+                .max_by_key(|&(score, _)| score)?;
+
+            Some(DesktopMatch {
+                space_id: desktop.id,
+                display_index: desktop.space.display,
+                matched: matched.to_string(),
+                name,
+                title,
+                score,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score));
+    matches
+}
+
+/// Fuzzy-find a desktop by name, title or todo text and switch to it on its
+/// display. Ambiguous queries return the candidates without switching.
+#[tauri::command]
+async fn switch_to_desktop_by_query(app: tauri::AppHandle, query: String) -> QuerySwitch {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let desktops = current_desktops(&state);
+        let mut candidates = {
+            let data = state.data.lock().unwrap();
+            match_desktops(&data, &desktops, &query)
         };
-        let history = data.context_history.entry(sid).or_default();
-        history.push(entry);
-        // Cap at MAX_HISTORY_PER_DESKTOP (drop oldest)
+        let scores: Vec<i64> = candidates.iter().map(|c| c.score).collect();
+        let result = fuzzy::is_decisive(&scores).then(|| {
+            let best = &candidates[0];
+            perform_switch(&state, best.display_index, best.space_id)
+        });
+        candidates.truncate(MAX_QUERY_CANDIDATES);
+        QuerySwitch { result, candidates }
+    })
+    .await
+    .unwrap_or(QuerySwitch { result: None, candidates: Vec::new() })
+}
+
+// ── Navigation history ────────────────────────────────────────
+
+/// Back/forward history per display, indexed by display number.
+static NAV_HISTORY: Mutex<Vec<NavHistory>> = Mutex::new(Vec::new());
+/// Displays with a switch in flight. Stepping passes through intermediate
+/// spaces, which must not be recorded as visits.
+static NAV_PAUSED: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+fn with_nav<T>(display: usize, f: impl FnOnce(&mut NavHistory) -> T) -> T {
+    let mut nav = NAV_HISTORY.lock().unwrap();
+    if nav.len() <= display {
+        nav.resize_with(display + 1, NavHistory::default);
+    }
+    f(&mut nav[display])
+}
+
+fn with_navigation_paused<T>(display: usize, f: impl FnOnce() -> T) -> T {
+    NAV_PAUSED.lock().unwrap().push(display);
+    let result = f();
+    let mut paused = NAV_PAUSED.lock().unwrap();
+    if let Some(i) = paused.iter().position(|&d| d == display) {
+        paused.remove(i);
+    }
+    result
+}
+
+fn emit_navigation_changed(handle: &tauri::AppHandle) {
+    let nav = NAV_HISTORY.lock().unwrap().clone();
+    if let Err(e) = handle.emit("navigation-changed", &nav) {
+        log::error!("Failed to emit navigation-changed: {}", e);
+    }
+}
+
+/// Record the active desktop on every display that isn't mid-switch.
+fn record_visits(handle: &tauri::AppHandle, desktops: &[Desktop]) {
+    let state = handle.state::<AppState>();
+    let display_count = desktops.iter().map(|d| d.space.display).max().map_or(1, |m| m + 1);
+
+    let mut changed = false;
+    for display in 0..display_count {
+        if NAV_PAUSED.lock().unwrap().contains(&display) {
+            continue;
+        }
+        let (sid, _) = space_info_for_display(display);
+        if let Some(desktop) = desktops.iter().find(|d| d.space.space_id == sid) {
+            changed |= with_nav(display, |h| h.visit(desktop.id));
+            launch_pending_apps(&state, desktop.id, display);
+        }
+    }
+    if changed {
+        emit_navigation_changed(handle);
+    }
+}
+
+/// Move `offset` steps through the display's history, skipping entries for
+/// desktops that no longer exist. Returns `None` when there's nothing there.
+fn navigate(state: &AppState, display: usize, offset: isize) -> Option<switching::SwitchResult> {
+    use switching::SwitchResult;
+    loop {
+        let target = with_nav(display, |h| h.peek(offset))?;
+        // Move the cursor before recording resumes, so the observer sees the
+        // target as the current entry rather than as a new visit.
+        let result = with_navigation_paused(display, || {
+            let result = switch_to_desktop(state, display, target);
+            if matches!(result, SwitchResult::Switched | SwitchResult::AlreadyThere) {
+                with_nav(display, |h| h.go(offset));
+            }
+            result
+        });
+        match result {
+            SwitchResult::Switched | SwitchResult::AlreadyThere => {
+                emit_navigation_changed(&state.app_handle);
+                return Some(result);
+            }
+            SwitchResult::TargetNotOnDisplay => {
+                with_nav(display, |h| h.remove(offset));
+                emit_navigation_changed(&state.app_handle);
+            }
+            _ => return Some(result),
+        }
+    }
+}
+
+#[tauri::command]
+fn get_navigation_history() -> Vec<NavHistory> {
+    NAV_HISTORY.lock().unwrap().clone()
+}
+
+#[tauri::command]
+async fn go_back(app: tauri::AppHandle, display: u32) -> Option<switching::SwitchResult> {
+    tauri::async_runtime::spawn_blocking(move || navigate(&app.state::<AppState>(), display as usize, -1))
+        .await
+        .unwrap_or(None)
+}
+
+#[tauri::command]
+async fn go_forward(app: tauri::AppHandle, display: u32) -> Option<switching::SwitchResult> {
+    tauri::async_runtime::spawn_blocking(move || navigate(&app.state::<AppState>(), display as usize, 1))
+        .await
+        .unwrap_or(None)
+}
+
+/// Jump to the previously visited desktop. Unlike `go_back` this records a
+/// new visit, so calling it again returns to where you started.
+fn toggle_last(state: &AppState, display: usize) -> Option<switching::SwitchResult> {
+    loop {
+        let target = with_nav(display, |h| h.peek(-1))?;
+        let result = perform_switch(state, display, target);
+        if result != switching::SwitchResult::TargetNotOnDisplay {
+            return Some(result);
+        }
+        with_nav(display, |h| h.remove(-1));
+        emit_navigation_changed(&state.app_handle);
+    }
+}
+
+/// Jump back to the previous desktop on `display`; see `toggle_last`. Runs
+/// off the main thread since switching waits for the space change.
+#[tauri::command]
+async fn toggle_last_desktop(app: tauri::AppHandle, display: u32) -> Option<switching::SwitchResult> {
+    tauri::async_runtime::spawn_blocking(move || toggle_last(&app.state::<AppState>(), display as usize))
+        .await
+        .unwrap_or(None)
+}
+
+// ── Global hotkeys ────────────────────────────────────────────
+
+static HOTKEY_ERRORS: Mutex<Vec<HotkeyError>> = Mutex::new(Vec::new());
+
+/// Replace all registered global shortcuts with `bindings`. Returns the
+/// bindings that were rejected or couldn't be registered.
+fn register_hotkeys(app: &tauri::AppHandle, bindings: &HashMap<HotkeyAction, String>) -> Vec<HotkeyError> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        log::warn!("[hotkeys] failed to unregister shortcuts: {}", e);
+    }
+
+    let (valid, mut errors) = hotkeys::validate(bindings);
+    for (action, accel) in valid {
+        let registered = shortcuts.on_shortcut(accel.to_string().as_str(), move |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                run_hotkey_action(app, action);
+            }
+        });
+        if let Err(e) = registered {
+            log::warn!("[hotkeys] failed to register {} for {:?}: {}", accel, action, e);
+            errors.push(HotkeyError { action, accelerator: accel.to_string(), message: e.to_string() });
+        }
+    }
+
+    *HOTKEY_ERRORS.lock().unwrap() = errors.clone();
+    errors
+}
+
+/// Tray menu items whose labels follow window state.
+struct TrayItems {
+    toggle_all: MenuItem<tauri::Wry>,
+}
+
+/// Show or hide every window at once and update the tray's "Hide Entirely"
+/// label to match. Returns whether they are now visible.
+fn toggle_all_windows(app: &tauri::AppHandle) -> bool {
+    let windows = app.webview_windows();
+    let any_visible = windows.values().any(|w| w.is_visible().unwrap_or(false));
+    for window in windows.values() {
+        if any_visible {
+            window.hide().ok();
+        } else {
+            window.show().ok();
+            window.set_focus().ok();
+        }
+    }
+    if let Some(items) = app.try_state::<TrayItems>() {
+        items.toggle_all.set_text(if any_visible { "Show Entirely" } else { "Hide Entirely" }).ok();
+    }
+    !any_visible
+}
+
+/// Hotkeys act on the main display's window and desktop.
+fn run_hotkey_action(app: &tauri::AppHandle, action: HotkeyAction) {
+    log::info!("[hotkeys] {:?}", action);
+    match action {
+        HotkeyAction::ToggleWindows => {
+            toggle_all_windows(app);
+        }
+        HotkeyAction::PreviousDesktop => {
+            let app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || toggle_last(&app.state::<AppState>(), 0));
+        }
+        // Handled by the UI: bring the window forward and tell it what to do.
+        HotkeyAction::QuickAddTodo | HotkeyAction::StartTimer | HotkeyAction::OpenSwitcher => {
+            if let Some(window) = app.get_webview_window("main") {
+                window.show().ok();
+                window.set_focus().ok();
+            }
+            if let Err(e) = app.emit("hotkey-triggered", action) {
+                log::error!("Failed to emit hotkey-triggered: {}", e);
+            }
+        }
+    }
+}
+
+// ── Session commands ───────────────────────────────────────────
+
+const MAX_HISTORY_PER_DESKTOP: usize = 20;
+
+/// Summary of an archived session, emitted to the UI after an automatic rollover.
+#[derive(Serialize, Clone, Debug)]
+struct RolloverSummary {
+    archived_desktops: usize,
+    done_count: usize,
+    open_count: usize,
+    carried_count: usize,
+    rolled_at: String,
+}
+
+/// Push every desktop's current context into history, then clear the
+/// session, keeping the todos and titles selected by `carry`. Carried todos
+/// are reset to not done.
+fn archive_session(data: &mut PersistData, carry: CarryMode) -> RolloverSummary {
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut summary = RolloverSummary {
+        archived_desktops: 0,
+        done_count: 0,
+        open_count: 0,
+        carried_count: 0,
+        rolled_at: now.clone(),
+    };
+
+    // For each desktop with data, push current context into history
+    let space_ids: Vec<i64> = data.notes.keys().chain(data.titles.keys()).copied().collect::<HashSet<i64>>().into_iter().collect();
+
+    for sid in space_ids {
+        let title = data.titles.get(&sid).cloned().unwrap_or_default();
+        let todos = data.notes.get(&sid).cloned().unwrap_or_default();
+        if title.is_empty() && todos.is_empty() {
+            continue;
+        }
+        summary.archived_desktops += 1;
+        summary.done_count += todos.iter().filter(|t| t.done).count();
+        summary.open_count += todos.iter().filter(|t| !t.done).count();
+        let entry = SavedContext {
+            title,
+            todos,
+            saved_at: now.clone(),
+        };
+        let history = data.context_history.entry(sid).or_default();
+        history.push(entry);
+        // Cap at MAX_HISTORY_PER_DESKTOP (drop oldest)
         if history.len() > MAX_HISTORY_PER_DESKTOP {
             let excess = history.len() - MAX_HISTORY_PER_DESKTOP;
             history.drain(..excess);
         }
     }
 
-    // Clear current session
-    data.notes.clear();
-    data.titles.clear();
+    // Clear current session, carrying items forward per `carry`
+    let keep = |t: &TodoItem| match carry {
+        CarryMode::All => t.pinned || !t.done,
+        CarryMode::Pinned => t.pinned,
+        CarryMode::None => false,
+    };
+    data.notes = data
+        .notes
+        .drain()
+        .filter_map(|(sid, todos)| {
+            let carried: Vec<TodoItem> = todos
+                .into_iter()
+                .filter(keep)
+                .map(|t| TodoItem { done: false, ..t })
+                .collect();
+            if carried.is_empty() { None } else { Some((sid, carried)) }
+        })
+        .collect();
+    summary.carried_count = data.notes.values().map(|t| t.len()).sum();
+    match carry {
+        CarryMode::All => {}
+        CarryMode::Pinned => data.titles.retain(|sid, _| data.pinned_titles.contains(sid)),
+        CarryMode::None => data.titles.clear(),
+    }
+    summary
+}
 
+#[tauri::command]
+fn start_new_session(state: tauri::State<'_, AppState>) {
+    let mut data = state.data.lock().unwrap();
+    archive_session(&mut data, CarryMode::Pinned);
     let path = state.data_path.lock().unwrap();
     persist_data(&path, &data);
 }
@@ -671,6 +1589,9 @@ fn restore_context(state: tauri::State<'_, AppState>, desktop: i64, index: usize
     } else {
         data.titles.insert(desktop, saved.title);
     }
+    if data.settings.launch_apps_on_restore {
+        arm_app_launch([desktop]);
+    }
     let path = state.data_path.lock().unwrap();
     persist_data(&path, &data);
     true
@@ -704,8 +1625,24 @@ fn save_color(state: tauri::State<'_, AppState>, desktop: i64, color: String) {
     emit_settings_changed(&state);
 }
 
+#[tauri::command]
+fn save_desktop_name(state: tauri::State<'_, AppState>, desktop: i64, name: String) {
+    let mut data = state.data.lock().unwrap();
+    let name = name.trim();
+    if name.is_empty() {
+        data.settings.custom_names.remove(&desktop);
+    } else {
+        data.settings.custom_names.insert(desktop, name.to_string());
+    }
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+}
+
 #[derive(Serialize, Clone)]
 struct SpaceInfo {
+    /// Stable desktop id, as in `DesktopInfo`.
     space_id: i64,
     position: u32,
     name: String,
@@ -715,22 +1652,18 @@ struct SpaceInfo {
 
 #[tauri::command]
 fn list_all_spaces(state: tauri::State<'_, AppState>) -> Vec<SpaceInfo> {
-    let spaces = enumerate_spaces();
+    let desktops = current_desktops(&state);
     let data = state.data.lock().unwrap();
-    spaces
+    desktops
         .iter()
-        .enumerate()
-        .map(|(pos, &(sid, _disp, _local, _fs))| {
-            let position = pos as u32;
-            let color = data.settings.custom_colors.get(&sid)
-                .cloned()
-                .unwrap_or_else(|| default_color(position));
+        .map(|desktop| {
+            let data_id = desktop.data_id(&data.settings);
             SpaceInfo {
-                space_id: sid,
-                position,
-                name: format!("Desktop {}", position + 1),
-                title: data.titles.get(&sid).cloned().unwrap_or_default(),
-                color,
+                space_id: desktop.id,
+                position: desktop.slot.position,
+                name: desktop.name(&data.settings),
+                title: data.titles.get(&data_id).cloned().unwrap_or_default(),
+                color: desktop.color(&data.settings),
             }
         })
         .collect()
@@ -748,12 +1681,16 @@ fn save_desktop_count(state: tauri::State<'_, AppState>, count: u32) {
 
 #[tauri::command]
 fn apply_theme(state: tauri::State<'_, AppState>, colors: Vec<String>) {
-    let spaces = enumerate_spaces();
+    let desktops = current_desktops(&state);
     let mut data = state.data.lock().unwrap();
+    let exclude_fullscreen = data.settings.exclude_fullscreen;
     data.settings.custom_colors.clear();
-    for (i, &(sid, _disp, _local, _fs)) in spaces.iter().enumerate() {
-        if i < colors.len() {
-            data.settings.custom_colors.insert(sid, colors[i].clone());
+    for desktop in &desktops {
+        if exclude_fullscreen && desktop.space.is_fullscreen {
+            continue;
+        }
+        if let Some(color) = colors.get(desktop.slot.position as usize) {
+            data.settings.custom_colors.insert(desktop.id, color.clone());
         }
     }
     let path = state.data_path.lock().unwrap();
@@ -762,6 +1699,56 @@ fn apply_theme(state: tauri::State<'_, AppState>, colors: Vec<String>) {
     emit_settings_changed(&state);
 }
 
+#[tauri::command]
+fn save_fullscreen_settings(state: tauri::State<'_, AppState>, exclude: bool, todos_to_parent: bool) {
+    let mut data = state.data.lock().unwrap();
+    data.settings.exclude_fullscreen = exclude;
+    data.settings.fullscreen_todos_to_parent = todos_to_parent;
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+}
+
+#[derive(Serialize, Clone)]
+struct HotkeyReport {
+    /// False when validation failed and nothing was changed.
+    saved: bool,
+    errors: Vec<HotkeyError>,
+}
+
+/// Validate and save the hotkey bindings, then re-register them. Invalid,
+/// reserved or conflicting bindings reject the whole save; bindings the OS
+/// refuses (e.g. taken by another app) are saved but reported.
+#[tauri::command]
+fn save_hotkeys(state: tauri::State<'_, AppState>, hotkeys: HashMap<HotkeyAction, String>) -> HotkeyReport {
+    let (_, errors) = hotkeys::validate(&hotkeys);
+    if !errors.is_empty() {
+        return HotkeyReport { saved: false, errors };
+    }
+
+    // Store the canonical form so the UI shows what was registered.
+    let hotkeys: HashMap<HotkeyAction, String> = hotkeys
+        .into_iter()
+        .filter_map(|(action, text)| hotkeys::Accelerator::parse(&text).ok().map(|a| (action, a.to_string())))
+        .collect();
+
+    let mut data = state.data.lock().unwrap();
+    data.settings.hotkeys = hotkeys.clone();
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+
+    HotkeyReport { saved: true, errors: register_hotkeys(&state.app_handle, &hotkeys) }
+}
+
+/// Errors from the last hotkey registration, including the one at launch.
+#[tauri::command]
+fn get_hotkey_errors() -> Vec<HotkeyError> {
+    HOTKEY_ERRORS.lock().unwrap().clone()
+}
+
 #[tauri::command]
 fn save_timer_presets(state: tauri::State<'_, AppState>, presets: Vec<u32>) {
     let mut data = state.data.lock().unwrap();
@@ -803,6 +1790,26 @@ fn save_auto_hide_delay(state: tauri::State<'_, AppState>, delay: u32) {
     emit_settings_changed(&state);
 }
 
+#[tauri::command]
+fn save_rollover_settings(state: tauri::State<'_, AppState>, time: Option<String>, carry: CarryMode) -> Result<(), String> {
+    if let Some(t) = &time {
+        parse_rollover_time(t)?;
+    }
+    let mut data = state.data.lock().unwrap();
+    // Start counting from now so enabling rollover doesn't immediately fire
+    // for a time that already passed today.
+    if data.settings.rollover_time.is_none() && time.is_some() {
+        data.last_rollover = Some(chrono::Local::now().to_rfc3339());
+    }
+    data.settings.rollover_time = time;
+    data.settings.rollover_carry = carry;
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+    Ok(())
+}
+
 #[tauri::command]
 fn get_common_apps(state: tauri::State<'_, AppState>) -> Vec<CommonApp> {
     let data = state.data.lock().unwrap();
@@ -810,8 +1817,19 @@ fn get_common_apps(state: tauri::State<'_, AppState>) -> Vec<CommonApp> {
 }
 
 #[tauri::command]
-fn save_common_apps(state: tauri::State<'_, AppState>, apps: Vec<CommonApp>) {
+fn save_common_apps(state: tauri::State<'_, AppState>, mut apps: Vec<CommonApp>) {
+    apps.iter_mut().for_each(CommonApp::fill_bundle_id);
+    dedupe_common_apps(&mut apps);
     let mut data = state.data.lock().unwrap();
+    // Newly added apps get their launch recipe, as with `add_common_app`
+    let mut added = apps.iter_mut().filter(|app| !data.settings.common_apps.iter().any(|a| a.same_app(app))).peekable();
+    if added.peek().is_some() {
+        let registry = load_recipes(&state);
+        for app in added {
+            apply_recipe(app, &registry.recipes);
+        }
+    }
+    normalize_common_apps(&mut apps, &data.settings.app_groups);
     data.settings.common_apps = apps;
     let path = state.data_path.lock().unwrap();
     persist_data(&path, &data);
@@ -835,59 +1853,270 @@ fn save_dismissed_tips(state: tauri::State<'_, AppState>, tips: Vec<String>) {
     emit_settings_changed(&state);
 }
 
+/// Rescan for installed apps after this long, even without `refresh`.
+const INSTALLED_APPS_TTL_SECS: u64 = 10 * 60;
+
+static INSTALLED_APPS: Mutex<Option<(std::time::Instant, Vec<apps::InstalledApp>)>> = Mutex::new(None);
+
+/// Apps from `/Applications`, `/System/Applications` and `~/Applications`,
+/// including nested folders. Results are cached; pass `refresh` to rescan.
 #[tauri::command]
-fn list_installed_apps() -> Vec<CommonApp> {
-    let mut apps = Vec::new();
-    if let Ok(entries) = fs::read_dir("/Applications") {
+async fn list_installed_apps(refresh: Option<bool>) -> Vec<apps::InstalledApp> {
+    tauri::async_runtime::spawn_blocking(move || installed_apps(refresh.unwrap_or(false)))
+        .await
+        .unwrap_or_default()
+}
+
+fn installed_apps(refresh: bool) -> Vec<apps::InstalledApp> {
+    let mut cache = INSTALLED_APPS.lock().unwrap();
+    let ttl = std::time::Duration::from_secs(INSTALLED_APPS_TTL_SECS);
+    match cache.as_ref() {
+        Some((scanned, apps)) if !refresh && scanned.elapsed() < ttl => apps.clone(),
+        _ => {
+            let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+            let found = apps::discover(&apps::app_roots(home.as_deref()));
+            log::info!("[apps] found {} installed apps", found.len());
+            *cache = Some((std::time::Instant::now(), found.clone()));
+            found
+        }
+    }
+}
+
+/// Installed apps for one batch of lookups. The cache is read on first use,
+/// and an app missing from it triggers at most one rescan for the batch.
+#[derive(Default)]
+struct AppScan {
+    apps: Option<Vec<apps::InstalledApp>>,
+    rescanned: bool,
+}
+
+impl AppScan {
+    /// Current location of an app, by bundle id.
+    fn resolve(&mut self, path: &str, bundle_id: Option<&str>) -> Option<String> {
+        let found = apps::resolve(path, bundle_id, || self.apps.get_or_insert_with(|| installed_apps(false)).as_slice());
+        if found.is_some() || bundle_id.is_none() || self.rescanned {
+            return found;
+        }
+        self.rescanned = true;
+        let apps = self.apps.insert(installed_apps(true));
+        apps::resolve(path, bundle_id, || apps.as_slice())
+    }
+}
+
+/// Resolve where `bundle_id` lives now and, if it moved, update every common
+/// app and app set entry for it. Falls back to `path` when it can't be found.
+fn locate_app(state: &AppState, scan: &mut AppScan, path: &str, bundle_id: Option<&str>) -> String {
+    let Some(id) = bundle_id else {
+        return path.to_string();
+    };
+    let Some(current) = scan.resolve(path, Some(id)) else {
+        return path.to_string();
+    };
+    if current != path {
+        log::info!("[apps] {} moved from {} to {}", id, path, current);
+        let mut data = state.data.lock().unwrap();
+        let store = &mut *data;
+        let apps = store.settings.common_apps.iter_mut().chain(store.app_sets.values_mut().flatten());
+        for app in apps.filter(|a| a.bundle_id.as_deref().is_some_and(|b| b.eq_ignore_ascii_case(id))) {
+            app.path = current.clone();
+        }
+        let data_path = state.data_path.lock().unwrap();
+        persist_data(&data_path, &data);
+        drop(data_path); drop(data);
+        emit_settings_changed(state);
+    }
+    current
+}
+
+// ── App icons ───────────────────────────────────────────────────────────────
+
+/// Sizes icons are rendered at. Requests are rounded up to the next one.
+const ICON_SIZES: &[u32] = &[16, 32, 64, 128, 256];
+
+/// The bundle's `.icns` file and the key its cached PNGs are stored under.
+fn icon_source(app_path: &std::path::Path) -> Option<(std::path::PathBuf, String)> {
+    let bundle = apps::read_bundle(app_path)?;
+    let mut file = bundle.icon_file?;
+    if std::path::Path::new(&file).extension().is_none() {
+        file.push_str(".icns");
+    }
+    let icns = app_path.join("Contents").join("Resources").join(file);
+    let key: String = bundle
+        .bundle_id
+        .unwrap_or(bundle.name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    Some((icns, key))
+}
+
+/// PNG bytes for an app's icon, rendered once per size and cached as
+/// `icons/{bundle id}-{icns mtime}-{size}.png`. Renders for an older mtime
+/// are removed when the icon changes.
+fn app_icon_png(cache_dir: &std::path::Path, app_path: &std::path::Path, size: u32) -> Result<Vec<u8>, String> {
+    let size = ICON_SIZES.iter().copied().find(|&s| s >= size).unwrap_or(*ICON_SIZES.last().unwrap());
+    let (icns_path, key) = icon_source(app_path).ok_or("App has no icon")?;
+    let mtime = fs::metadata(&icns_path)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("{}: {}", icns_path.display(), e))?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let cached = cache_dir.join(format!("{}-{}-{}.png", key, mtime, size));
+    if let Ok(bytes) = fs::read(&cached) {
+        return Ok(bytes);
+    }
+
+    let png = icns::render_png(&fs::read(&icns_path).map_err(|e| e.to_string())?, size)?;
+    fs::create_dir_all(cache_dir).ok();
+    let prefix = format!("{}-", key);
+    let current = format!("{}{}-", prefix, mtime);
+    if let Ok(entries) = fs::read_dir(cache_dir) {
         for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("app") {
-                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
-                    apps.push(CommonApp {
-                        name: name.to_string(),
-                        path: path.to_string_lossy().to_string(),
-                        short_name: None,
-                        launch_args: None,
-                    });
-                }
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Only `{key}-{mtime}-{size}.png`, so a key that merely starts
+            // with this one is left alone.
+            let ours = name.strip_prefix(&prefix).and_then(|rest| rest.strip_suffix(".png")).is_some_and(|rest| {
+                let parts: Vec<&str> = rest.split('-').collect();
+                parts.len() == 2 && parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+            });
+            if ours && !name.starts_with(&current) {
+                fs::remove_file(entry.path()).ok();
             }
         }
     }
-    apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    apps
+    if let Err(e) = fs::write(&cached, &png) {
+        log::warn!("[icons] failed to cache {}: {}", cached.display(), e);
+    }
+    Ok(png)
+}
+
+/// Serves `appicon://localhost/{size}?path={app path}` as a PNG.
+fn serve_app_icon(app: &tauri::AppHandle, request: &tauri::http::Request<Vec<u8>>) -> tauri::http::Response<Vec<u8>> {
+    let respond = |status: u16, content_type: &str, body: Vec<u8>| {
+        tauri::http::Response::builder()
+            .status(status)
+            .header("Content-Type", content_type)
+            .header("Cache-Control", "max-age=3600")
+            .body(body)
+            .unwrap()
+    };
+
+    let Ok(url) = tauri::Url::parse(&request.uri().to_string()) else {
+        return respond(400, "text/plain", b"Bad URL".to_vec());
+    };
+    let size = url.path().trim_matches('/').parse::<u32>().unwrap_or(32);
+    let Some(path) = url.query_pairs().find(|(k, _)| k == "path").map(|(_, v)| v.to_string()) else {
+        return respond(400, "text/plain", b"Missing path".to_vec());
+    };
+    let Ok(data_dir) = app.path().app_data_dir() else {
+        return respond(500, "text/plain", b"No app data dir".to_vec());
+    };
+
+    match app_icon_png(&data_dir.join("icons"), std::path::Path::new(&path), size) {
+        Ok(png) => respond(200, "image/png", png),
+        Err(e) => {
+            log::debug!("[icons] {}: {}", path, e);
+            respond(404, "text/plain", e.into_bytes())
+        }
+    }
+}
+
+/// Title of the desktop with keyboard focus, for launch templates.
+fn focused_desktop_title(state: &AppState) -> String {
+    let active_sid = unsafe { CGSGetActiveSpace(CGSMainConnectionID()) };
+    let desktops = current_desktops(state);
+    let data = state.data.lock().unwrap();
+    desktops
+        .iter()
+        .find(|d| d.space.space_id == active_sid)
+        .and_then(|d| data.titles.get(&d.data_id(&data.settings)).cloned())
+        .unwrap_or_default()
 }
 
 #[tauri::command]
-fn open_new_app_instance(path: String, launch_args: Option<String>) -> Result<(), String> {
-    if let Some(args) = launch_args {
-        // Custom launch args: run as a shell command so users can use any CLI tool
-        // e.g. "code --new-window" or "open -a Chrome --args --new-window"
-        std::process::Command::new("sh")
-            .arg("-c")
-            .arg(&args)
-            .output()
-            .map_err(|e| format!("Failed to run custom launch command: {}", e))?;
+fn open_new_app_instance(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    launch_args: Option<String>,
+    raw_shell: Option<bool>,
+    bundle_id: Option<String>,
+) -> Result<launch::LaunchReport, String> {
+    let path = locate_app(&state, &mut AppScan::default(), &path, bundle_id.as_deref());
+    open_new_instance(&state, path, launch_args, raw_shell.unwrap_or(false))
+}
+
+/// Open a new instance of the app at `path`, through its launch command if
+/// it has one.
+fn open_new_instance(
+    state: &AppState,
+    path: String,
+    launch_args: Option<String>,
+    raw_shell: bool,
+) -> Result<launch::LaunchReport, String> {
+    let report = if let Some(command) = launch_args.filter(|c| !c.trim().is_empty()) {
+        let app_name = std::path::Path::new(&path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_string();
+        let desktop_title = focused_desktop_title(state);
+        let ctx = launch::LaunchContext { path: &path, app_name: &app_name, desktop_title: &desktop_title };
+        let argv = launch::build_argv(&command, raw_shell, &ctx)?;
+        let strategy = if raw_shell { launch::LaunchStrategy::Shell } else { launch::LaunchStrategy::Command };
+        launch::run(&path, strategy, argv)
     } else {
-        std::process::Command::new("open")
-            .arg("-n")
-            .arg(&path)
-            .output()
-            .map_err(|e| format!("Failed to open new app instance: {}", e))?;
+        launch::run(&path, launch::LaunchStrategy::OpenNew, vec!["open".into(), "-n".into(), path.clone()])
+    };
+    Ok(log_launch(report))
+}
+
+/// Check a custom launch command for the settings UI. Returns the argv it
+/// would run, with placeholders unexpanded.
+#[tauri::command]
+fn validate_launch_command(command: String, raw_shell: bool) -> Result<Vec<String>, String> {
+    launch::validate(&command, raw_shell)
+}
+
+/// Keep this many launches in the log.
+const MAX_LAUNCH_LOG: usize = 50;
+
+/// Recent launches, newest last, for troubleshooting launch commands.
+static LAUNCH_LOG: Mutex<Vec<launch::LaunchReport>> = Mutex::new(Vec::new());
+
+fn log_launch(report: launch::LaunchReport) -> launch::LaunchReport {
+    if let Some(error) = report.error() {
+        log::warn!("[launch] {:?} failed for {}: {}", report.strategy, report.path, error);
     }
-    Ok(())
+    let mut log = LAUNCH_LOG.lock().unwrap();
+    log.push(report.clone());
+    if log.len() > MAX_LAUNCH_LOG {
+        let excess = log.len() - MAX_LAUNCH_LOG;
+        log.drain(..excess);
+    }
+    report
 }
 
 #[tauri::command]
-fn launch_app(path: String) -> Result<(), String> {
-    std::process::Command::new("open")
-        .arg(&path)
-        .output()
-        .map_err(|e| format!("Failed to launch app: {}", e))?;
-    Ok(())
+fn get_launch_log() -> Vec<launch::LaunchReport> {
+    LAUNCH_LOG.lock().unwrap().iter().rev().cloned().collect()
+}
+
+#[tauri::command]
+fn clear_launch_log() {
+    LAUNCH_LOG.lock().unwrap().clear();
 }
 
 #[tauri::command]
-fn launch_app_new(path: String, monitor_x: i32, monitor_y: i32) -> Result<(), String> {
+fn launch_app(path: String) -> launch::LaunchReport {
+    log_launch(launch::run(&path, launch::LaunchStrategy::Open, vec!["open".into(), path.clone()]))
+}
+
+#[tauri::command]
+fn launch_app_new(path: String, monitor_x: i32, monitor_y: i32) -> launch::LaunchReport {
+    let open_new = |path: &str| launch::run(path, launch::LaunchStrategy::OpenNew, vec!["open".into(), "-n".into(), path.into()]);
+
     // Extract app name from path (e.g. "/Applications/Safari.app" -> "Safari")
     let app_name = std::path::Path::new(&path)
         .file_stem()
@@ -896,71 +2125,479 @@ fn launch_app_new(path: String, monitor_x: i32, monitor_y: i32) -> Result<(), St
         .to_string();
 
     if app_name.is_empty() {
-        std::process::Command::new("open")
-            .arg("-n")
-            .arg(&path)
-            .output()
-            .map_err(|e| format!("Failed to launch new app instance: {}", e))?;
-        return Ok(());
+        return log_launch(open_new(&path));
     }
 
     // Activate the app on the current desktop, send Cmd+N for a new window,
     // then move the frontmost window to the target monitor
     let script = format!(
-        r#"tell application "{app}" to activate
+        r#"tell application {app} to activate
 delay 0.3
 tell application "System Events" to keystroke "n" using command down
 delay 0.3
 tell application "System Events"
-    tell process "{app}"
+    tell process {app}
         try
             set position of front window to {{{x}, {y}}}
         end try
     end tell
 end tell"#,
-        app = app_name,
+        app = launch::applescript_string(&app_name),
         x = monitor_x,
         y = monitor_y,
     );
 
-    let result = std::process::Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output();
+    let scripted = launch::run(&path, launch::LaunchStrategy::NewWindowScript, vec!["osascript".into(), "-e".into(), script]);
+    if scripted.success {
+        return log_launch(scripted);
+    }
+    let mut report = open_new(&path);
+    report.fallback_from = Some(Box::new(scripted));
+    log_launch(report)
+}
 
-    match result {
-        Ok(output) if output.status.success() => Ok(()),
-        _ => {
-            std::process::Command::new("open")
-                .arg("-n")
-                .arg(&path)
-                .output()
-                .map_err(|e| format!("Failed to launch new app instance: {}", e))?;
-            Ok(())
+// ── Launch recipes ──────────────────────────────────────────
+
+const RECIPE_OVERRIDES_FILE: &str = "launch-recipes.json";
+
+#[derive(Serialize, Clone, Debug)]
+struct RecipeRegistry {
+    version: u32,
+    /// Where user recipes are read from. The file may not exist.
+    overrides_path: String,
+    /// Why the overrides file was ignored, if it was.
+    overrides_error: Option<String>,
+    recipes: Vec<recipes::Recipe>,
+}
+
+/// Built-in recipes merged with the user's overrides file, which is re-read
+/// on every call so edits apply without a restart.
+fn load_recipes(state: &AppState) -> RecipeRegistry {
+    let overrides_path = state
+        .app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(RECIPE_OVERRIDES_FILE))
+        .unwrap_or_default();
+    let (overrides, overrides_error) = match fs::read_to_string(&overrides_path) {
+        Ok(text) => match recipes::parse_overrides(&text) {
+            Ok(overrides) => (overrides, None),
+            Err(e) => {
+                log::warn!("[recipes] ignoring {}: {}", overrides_path.display(), e);
+                (Vec::new(), Some(e))
+            }
+        },
+        Err(_) => (Vec::new(), None),
+    };
+    RecipeRegistry {
+        version: recipes::REGISTRY_VERSION,
+        overrides_path: overrides_path.to_string_lossy().to_string(),
+        overrides_error,
+        recipes: recipes::merged(&overrides),
+    }
+}
+
+#[tauri::command]
+fn get_launch_recipes(state: tauri::State<'_, AppState>) -> RecipeRegistry {
+    load_recipes(&state)
+}
+
+/// Fill in `app`'s launch command from the recipe for its bundle id, unless
+/// it already has one. Returns the recipe, if there is one.
+fn apply_recipe(app: &mut CommonApp, recipes: &[recipes::Recipe]) -> Option<recipes::Recipe> {
+    let recipe = app.bundle_id.as_deref().and_then(|id| recipes::find(recipes, id).cloned());
+    let has_command = app.launch_args.as_deref().is_some_and(|c| !c.trim().is_empty());
+    if let Some(recipes::Strategy::Command { command, raw_shell }) = recipe.as_ref().map(|r| &r.strategy) {
+        if !has_command {
+            app.launch_args = Some(command.clone());
+            app.raw_shell = *raw_shell;
         }
     }
+    recipe
 }
 
+/// Add `app` to the common apps. Unless it already has a launch command,
+/// the recipe for its bundle id is applied; the recipe is returned so the
+/// UI can say when new windows aren't supported.
 #[tauri::command]
-fn add_common_app(state: tauri::State<'_, AppState>, app: CommonApp) {
+fn add_common_app(state: tauri::State<'_, AppState>, mut app: CommonApp) -> Option<recipes::Recipe> {
+    app.fill_bundle_id();
+    let recipe = apply_recipe(&mut app, &load_recipes(&state).recipes);
+
     let mut data = state.data.lock().unwrap();
-    if !data.settings.common_apps.iter().any(|a| a.path == app.path) {
+    if !data.settings.common_apps.iter().any(|a| a.same_app(&app)) {
         data.settings.common_apps.push(app);
+        let store = &mut *data;
+        normalize_common_apps(&mut store.settings.common_apps, &store.settings.app_groups);
         let path = state.data_path.lock().unwrap();
         persist_data(&path, &data);
         drop(path); drop(data);
         emit_settings_changed(&state);
     }
+    recipe
+}
+
+/// Remove a common app by bundle id, or by path for apps without one.
+#[tauri::command]
+fn remove_common_app(state: tauri::State<'_, AppState>, app_path: String, bundle_id: Option<String>) {
+    let target = CommonApp { path: app_path, bundle_id, ..Default::default() };
+    let mut data = state.data.lock().unwrap();
+    data.settings.common_apps.retain(|a| !a.same_app(&target));
+    let store = &mut *data;
+    normalize_common_apps(&mut store.settings.common_apps, &store.settings.app_groups);
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
 }
 
+/// Move the apps named by `keys` (see `CommonApp::key`) to the front, in
+/// that order. Apps not listed keep their relative order after them.
 #[tauri::command]
-fn remove_common_app(state: tauri::State<'_, AppState>, app_path: String) {
+fn reorder_common_apps(state: tauri::State<'_, AppState>, keys: Vec<String>) -> Vec<CommonApp> {
     let mut data = state.data.lock().unwrap();
-    data.settings.common_apps.retain(|a| a.path != app_path);
+    let store = &mut *data;
+    let apps = &mut store.settings.common_apps;
+    apps.sort_by_key(|app| keys.iter().position(|k| *k == app.key()).unwrap_or(keys.len()));
+    normalize_common_apps(apps, &store.settings.app_groups);
+    let result = apps.clone();
     let path = state.data_path.lock().unwrap();
     persist_data(&path, &data);
     drop(path); drop(data);
     emit_settings_changed(&state);
+    result
+}
+
+/// Replace the common app groups. New groups may leave `id` empty; apps in a
+/// removed group become ungrouped.
+#[tauri::command]
+fn save_app_groups(state: tauri::State<'_, AppState>, groups: Vec<AppGroup>) -> Result<Vec<AppGroup>, String> {
+    let mut seen = HashSet::new();
+    let mut saved = Vec::with_capacity(groups.len());
+    for mut group in groups {
+        group.name = group.name.trim().to_string();
+        if group.name.is_empty() {
+            return Err("Group name is empty".to_string());
+        }
+        if !seen.insert(group.name.to_lowercase()) {
+            return Err(format!("Duplicate group '{}'", group.name));
+        }
+        if group.id.is_empty() {
+            group.id = uuid::Uuid::new_v4().to_string();
+        }
+        saved.push(group);
+    }
+
+    let mut data = state.data.lock().unwrap();
+    let store = &mut *data;
+    store.settings.app_groups = saved.clone();
+    normalize_common_apps(&mut store.settings.common_apps, &store.settings.app_groups);
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+    Ok(saved)
+}
+
+/// Top-left corner of `monitor` in logical points, the units window
+/// positions are set in.
+fn monitor_origin(monitor: &tauri::Monitor) -> (i32, i32) {
+    let origin = monitor.position().to_logical::<i32>(monitor.scale_factor());
+    (origin.x, origin.y)
+}
+
+/// Open a common app with its default action. `window` is the clicked
+/// indicator window, whose monitor `NewWindowOnMonitor` targets. A failed
+/// launch is an error carrying the report's reason; the full report is in
+/// the launch log.
+#[tauri::command]
+async fn launch_common_app(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    key: String,
+) -> Result<launch::LaunchReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let entry = state
+            .data
+            .lock()
+            .unwrap()
+            .settings
+            .common_apps
+            .iter()
+            .find(|a| a.key() == key)
+            .cloned()
+            .ok_or_else(|| format!("No common app '{}'", key))?;
+        let path = locate_app(&state, &mut AppScan::default(), &entry.path, entry.bundle_id.as_deref());
+        let report = match entry.default_action {
+            AppAction::ActivateExisting => launch_app(path),
+            AppAction::NewWindow => open_new_instance(&state, path, entry.launch_args, entry.raw_shell)?,
+            AppAction::NewWindowOnMonitor => {
+                let (x, y) = window.current_monitor().ok().flatten().map_or((0, 0), |m| monitor_origin(&m));
+                launch_app_new(path, x, y)
+            }
+        };
+        match report.error() {
+            Some(error) => Err(format!("{}: {}", entry.name, error)),
+            None => Ok(report),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum AppHealthStatus {
+    Ok,
+    /// Found under a different path; the next launch updates the entry.
+    Moved,
+    Missing,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct CommonAppHealth {
+    name: String,
+    path: String,
+    bundle_id: Option<String>,
+    status: AppHealthStatus,
+    /// Where the app was found, if anywhere.
+    current_path: Option<String>,
+}
+
+/// Check that every common app's bundle can still be found.
+#[tauri::command]
+async fn check_common_apps(app: tauri::AppHandle) -> Vec<CommonAppHealth> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let common_apps = app.state::<AppState>().data.lock().unwrap().settings.common_apps.clone();
+        let mut scan = AppScan::default();
+        common_apps
+            .into_iter()
+            .map(|entry| {
+                let current_path = scan.resolve(&entry.path, entry.bundle_id.as_deref());
+                let status = match &current_path {
+                    Some(current) if *current == entry.path => AppHealthStatus::Ok,
+                    Some(_) => AppHealthStatus::Moved,
+                    None => AppHealthStatus::Missing,
+                };
+                if status != AppHealthStatus::Ok {
+                    log::warn!("[apps] common app {} is {:?}", entry.name, status);
+                }
+                CommonAppHealth { name: entry.name, path: entry.path, bundle_id: entry.bundle_id, status, current_path }
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+// ── Per-desktop app sets ──────────────────────────────────────
+
+/// Desktops whose app set still has to open on their first visit since the
+/// session was continued or restored.
+static PENDING_APP_LAUNCH: Mutex<Vec<i64>> = Mutex::new(Vec::new());
+
+#[derive(Serialize, Clone, Debug)]
+struct AppLaunchResult {
+    name: String,
+    path: String,
+    ok: bool,
+    error: Option<String>,
+    report: launch::LaunchReport,
+}
+
+fn arm_app_launch(desktops: impl IntoIterator<Item = i64>) {
+    let mut pending = PENDING_APP_LAUNCH.lock().unwrap();
+    for id in desktops {
+        if !pending.contains(&id) {
+            pending.push(id);
+        }
+    }
+}
+
+/// Top-left corner of the monitor showing `display`'s window, where new app
+/// windows are placed.
+fn display_origin(app: &tauri::AppHandle, display: usize) -> (i32, i32) {
+    let label = if display == 0 { "main".to_string() } else { format!("monitor-{}", display) };
+    app.get_webview_window(&label)
+        .and_then(|w| w.current_monitor().ok().flatten())
+        .map_or((0, 0), |m| monitor_origin(&m))
+}
+
+/// Open each app in `desktop`'s set on the active space of `display`.
+fn launch_app_set(state: &AppState, desktop: i64, display: usize) -> Vec<AppLaunchResult> {
+    let apps = state.data.lock().unwrap().app_sets.get(&desktop).cloned().unwrap_or_default();
+    let (x, y) = display_origin(&state.app_handle, display);
+    let mut scan = AppScan::default();
+    apps.into_iter()
+        .map(|app| {
+            let path = locate_app(state, &mut scan, &app.path, app.bundle_id.as_deref());
+            let report = launch_app_new(path.clone(), x, y);
+            let error = report.error();
+            if let Some(e) = &error {
+                log::warn!("[app-sets] failed to launch {} on desktop {}: {}", app.name, desktop, e);
+            }
+            AppLaunchResult { name: app.name, path, ok: report.success, error, report }
+        })
+        .collect()
+}
+
+/// Launch `desktop`'s app set if it was waiting for this visit.
+fn launch_pending_apps(state: &AppState, desktop: i64, display: usize) {
+    let mut pending = PENDING_APP_LAUNCH.lock().unwrap();
+    let Some(i) = pending.iter().position(|&id| id == desktop) else {
+        return;
+    };
+    pending.remove(i);
+    drop(pending);
+    let app = state.app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        launch_app_set(&app.state::<AppState>(), desktop, display);
+    });
+}
+
+#[tauri::command]
+fn get_desktop_app_set(state: tauri::State<'_, AppState>, desktop: i64) -> Vec<CommonApp> {
+    let data = state.data.lock().unwrap();
+    data.app_sets.get(&desktop).cloned().unwrap_or_default()
+}
+
+#[tauri::command]
+fn save_desktop_app_set(state: tauri::State<'_, AppState>, desktop: i64, mut apps: Vec<CommonApp>) {
+    apps.iter_mut().for_each(CommonApp::fill_bundle_id);
+    dedupe_common_apps(&mut apps);
+    normalize_common_apps(&mut apps, &[]);
+    let mut data = state.data.lock().unwrap();
+    if apps.is_empty() {
+        data.app_sets.remove(&desktop);
+    } else {
+        data.app_sets.insert(desktop, apps);
+    }
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+}
+
+#[tauri::command]
+fn save_launch_apps_on_restore(state: tauri::State<'_, AppState>, enabled: bool) {
+    let mut data = state.data.lock().unwrap();
+    data.settings.launch_apps_on_restore = enabled;
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+}
+
+/// Switch to `desktop` if needed, then open each app in its set there.
+#[tauri::command]
+async fn launch_desktop_apps(app: tauri::AppHandle, desktop: i64) -> Result<Vec<AppLaunchResult>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let display = current_desktops(&state)
+            .iter()
+            .find(|d| d.id == desktop)
+            .map(|d| d.space.display)
+            .ok_or_else(|| format!("Desktop {} does not exist", desktop))?;
+        match perform_switch(&state, display, desktop) {
+            switching::SwitchResult::Switched | switching::SwitchResult::AlreadyThere => {}
+            other => return Err(format!("Couldn't switch to desktop {}: {:?}", desktop, other)),
+        }
+        Ok(launch_app_set(&state, desktop, display))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// ── Bookmarks ──────────────────────────────────────────────
+
+/// The global list when `desktop` is `None`, otherwise that desktop's.
+fn bookmark_list(data: &mut PersistData, desktop: Option<i64>) -> &mut Vec<Bookmark> {
+    match desktop {
+        Some(id) => data.bookmarks.entry(id).or_default(),
+        None => &mut data.settings.bookmarks,
+    }
+}
+
+#[tauri::command]
+fn get_bookmarks(state: tauri::State<'_, AppState>, desktop: Option<i64>) -> Vec<Bookmark> {
+    let data = state.data.lock().unwrap();
+    match desktop {
+        Some(id) => data.bookmarks.get(&id).cloned().unwrap_or_default(),
+        None => data.settings.bookmarks.clone(),
+    }
+}
+
+/// Validate and save a bookmark. Paths may start with `~`; the saved value
+/// is the expanded path or normalised URL.
+#[tauri::command]
+fn add_bookmark(
+    state: tauri::State<'_, AppState>,
+    desktop: Option<i64>,
+    kind: BookmarkKind,
+    value: String,
+    label: Option<String>,
+) -> Result<Bookmark, String> {
+    let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+    let value = bookmarks::validate(kind, &value, home.as_deref())?;
+    let label = label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| bookmarks::default_label(kind, &value));
+    let bookmark = Bookmark { id: uuid::Uuid::new_v4().to_string(), kind, label, value };
+
+    let mut data = state.data.lock().unwrap();
+    let list = bookmark_list(&mut data, desktop);
+    if list.iter().any(|b| b.kind == bookmark.kind && b.value == bookmark.value) {
+        return Err(format!("{} is already bookmarked", bookmark.value));
+    }
+    list.push(bookmark.clone());
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+    Ok(bookmark)
+}
+
+#[tauri::command]
+fn remove_bookmark(state: tauri::State<'_, AppState>, desktop: Option<i64>, id: String) {
+    let mut data = state.data.lock().unwrap();
+    bookmark_list(&mut data, desktop).retain(|b| b.id != id);
+    if let Some(desktop) = desktop {
+        if data.bookmarks.get(&desktop).is_some_and(Vec::is_empty) {
+            data.bookmarks.remove(&desktop);
+        }
+    }
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+}
+
+/// Open a saved bookmark with its default handler. The stored value is
+/// validated again, since a file may have moved since it was saved.
+#[tauri::command]
+fn open_bookmark(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    desktop: Option<i64>,
+    id: String,
+) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+    let bookmark = {
+        let data = state.data.lock().unwrap();
+        match desktop {
+            Some(desktop) => data.bookmarks.get(&desktop).and_then(|list| list.iter().find(|b| b.id == id)).cloned(),
+            None => data.settings.bookmarks.iter().find(|b| b.id == id).cloned(),
+        }
+    }
+    .ok_or_else(|| format!("No bookmark '{}'", id))?;
+    let value = bookmarks::validate(bookmark.kind, &bookmark.value, None)?;
+    let result = match bookmark.kind {
+        BookmarkKind::Url => app.opener().open_url(value, None::<&str>),
+        _ => app.opener().open_path(value, None::<&str>),
+    };
+    result.map_err(|e| format!("Failed to open {}: {}", bookmark.label, e))
 }
 
 #[tauri::command]
@@ -968,9 +2605,168 @@ fn clear_all_data(state: tauri::State<'_, AppState>) {
     let mut data = state.data.lock().unwrap();
     data.notes.clear();
     data.titles.clear();
+    data.pinned_titles.clear();
     data.settings.custom_colors.clear();
+    data.settings.custom_names.clear();
+    data.app_sets.clear();
+    data.bookmarks.clear();
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+}
+
+// ── Orphaned desktop data ──────────────────────────────────────
+
+/// Per-desktop data whose desktop no longer exists.
+#[derive(Serialize, Clone, Debug)]
+struct OrphanedDesktop {
+    desktop_id: i64,
+    custom_name: Option<String>,
+    title: String,
+    todo_count: usize,
+    history_count: usize,
+    has_color: bool,
+    /// Where the desktop was last seen, if it was ever registered.
+    last_display: Option<usize>,
+    last_local: Option<u32>,
+}
+
+/// Desktop ids with data that don't belong to any current space.
+fn orphaned_ids(data: &PersistData, current: &HashSet<i64>) -> Vec<i64> {
+    let mut ids: Vec<i64> = store_keys(data).difference(current).copied().collect();
+    ids.sort();
+    ids
+}
+
+#[tauri::command]
+fn list_orphaned_desktops(state: tauri::State<'_, AppState>) -> Vec<OrphanedDesktop> {
+    let current: HashSet<i64> = current_desktops(&state).iter().map(|d| d.id).collect();
+    let data = state.data.lock().unwrap();
+    orphaned_ids(&data, &current)
+        .into_iter()
+        .map(|id| {
+            let record = data.desktops.iter().find(|r| r.id == id);
+            OrphanedDesktop {
+                desktop_id: id,
+                custom_name: data.settings.custom_names.get(&id).cloned(),
+                title: data.titles.get(&id).cloned().unwrap_or_default(),
+                todo_count: data.notes.get(&id).map_or(0, |t| t.iter().filter(|i| !i.done).count()),
+                history_count: data.context_history.get(&id).map_or(0, |h| h.len()),
+                has_color: data.settings.custom_colors.contains_key(&id),
+                last_display: record.map(|r| r.display),
+                last_local: record.map(|r| r.local),
+            }
+        })
+        .collect()
+}
+
+/// Move every piece of per-desktop data from `from` onto `to`. Todos and
+/// history are appended; `to` keeps its own title and color if it has them.
+fn merge_desktop_data(data: &mut PersistData, from: i64, to: i64) {
+    if let Some(mut todos) = data.notes.remove(&from) {
+        data.notes.entry(to).or_default().append(&mut todos);
+    }
+    if let Some(title) = data.titles.remove(&from) {
+        data.titles.entry(to).or_insert(title);
+    }
+    if data.pinned_titles.remove(&from) {
+        data.pinned_titles.insert(to);
+    }
+    if let Some(color) = data.settings.custom_colors.remove(&from) {
+        data.settings.custom_colors.entry(to).or_insert(color);
+    }
+    if let Some(name) = data.settings.custom_names.remove(&from) {
+        data.settings.custom_names.entry(to).or_insert(name);
+    }
+    if let Some(mut history) = data.context_history.remove(&from) {
+        let merged = data.context_history.entry(to).or_default();
+        merged.append(&mut history);
+        merged.sort_by(|a, b| a.saved_at.cmp(&b.saved_at));
+        if merged.len() > MAX_HISTORY_PER_DESKTOP {
+            let excess = merged.len() - MAX_HISTORY_PER_DESKTOP;
+            merged.drain(..excess);
+        }
+    }
+    if let Some(apps) = data.app_sets.remove(&from) {
+        let merged = data.app_sets.entry(to).or_default();
+        for app in apps {
+            if !merged.iter().any(|a| a.same_app(&app)) {
+                merged.push(app);
+            }
+        }
+    }
+    if let Some(apps) = data.app_usage.remove(&from) {
+        usage::merge(data.app_usage.entry(to).or_default(), apps);
+    }
+    if let Some(bookmarks) = data.bookmarks.remove(&from) {
+        let merged = data.bookmarks.entry(to).or_default();
+        for bookmark in bookmarks {
+            if !merged.iter().any(|b| b.kind == bookmark.kind && b.value == bookmark.value) {
+                merged.push(bookmark);
+            }
+        }
+    }
+    for item in data.completed.iter_mut().filter(|c| c.desktop_id == from) {
+        item.desktop_id = to;
+    }
+}
+
+/// Remove every piece of per-desktop data for `id`. The completed log keeps
+/// its entries, since they carry their own desktop name.
+fn remove_desktop_data(data: &mut PersistData, id: i64) {
+    data.notes.remove(&id);
+    data.titles.remove(&id);
+    data.pinned_titles.remove(&id);
+    data.settings.custom_colors.remove(&id);
+    data.settings.custom_names.remove(&id);
+    data.context_history.remove(&id);
+    data.app_sets.remove(&id);
+    data.app_usage.remove(&id);
+    data.bookmarks.remove(&id);
+}
+
+#[tauri::command]
+fn reassign_desktop_data(state: tauri::State<'_, AppState>, from: i64, to: i64) -> Result<(), String> {
+    if from == to {
+        return Err("Source and target desktop are the same".to_string());
+    }
+    let current: HashSet<i64> = current_desktops(&state).iter().map(|d| d.id).collect();
+    if !current.contains(&to) {
+        return Err(format!("Desktop {} does not exist", to));
+    }
+    let mut data = state.data.lock().unwrap();
+    merge_desktop_data(&mut data, from, to);
+    // A desktop that's gone has nothing left once its data moves
+    if !current.contains(&from) {
+        data.desktops.retain(|r| r.id != from);
+    }
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+    Ok(())
+}
+
+/// Delete all data for the orphaned desktops in `ids`, as listed by
+/// `list_orphaned_desktops` and confirmed by the user. Ids that aren't
+/// orphaned (any more) are skipped. Returns how many were purged.
+#[tauri::command]
+fn purge_orphans(state: tauri::State<'_, AppState>, ids: Vec<i64>) -> Result<usize, String> {
+    let current: HashSet<i64> = current_desktops(&state).iter().map(|d| d.id).collect();
+    // No spaces usually means CGS failed; everything would look orphaned
+    if current.is_empty() {
+        return Err("No desktops detected; refusing to purge".to_string());
+    }
+    let mut data = state.data.lock().unwrap();
+    let orphans: Vec<i64> = orphaned_ids(&data, &current).into_iter().filter(|id| ids.contains(id)).collect();
+    for &id in &orphans {
+        remove_desktop_data(&mut data, id);
+    }
+    data.desktops.retain(|r| !orphans.contains(&r.id));
     let path = state.data_path.lock().unwrap();
     persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+    Ok(orphans.len())
 }
 
 // ── Accessibility commands ─────────────────────────────────────
@@ -1009,27 +2805,214 @@ fn get_completed(state: tauri::State<'_, AppState>) -> Vec<CompletedItem> {
     data.completed.clone()
 }
 
-#[tauri::command]
-fn add_completed(state: tauri::State<'_, AppState>, text: String, desktop_id: i64, desktop_name: Option<String>) {
-    let mut data = state.data.lock().unwrap();
-    let item = CompletedItem {
+fn push_completed(data: &mut PersistData, text: String, desktop_id: i64, desktop_name: Option<String>) {
+    data.completed.push(CompletedItem {
         id: uuid::Uuid::new_v4().to_string(),
         text,
         desktop_id,
         desktop_name,
         completed_at: chrono::Utc::now().to_rfc3339(),
+    });
+}
+
+#[tauri::command]
+fn add_completed(state: tauri::State<'_, AppState>, text: String, desktop_id: i64, desktop_name: Option<String>) {
+    let mut data = state.data.lock().unwrap();
+    push_completed(&mut data, text, desktop_id, desktop_name);
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+}
+
+/// Log a todo as completed. Recurring todos are rescheduled to their next
+/// occurrence and left open; others, and series with no occurrences left,
+/// are marked done. Returns the updated list.
+#[tauri::command]
+fn complete_todo(state: tauri::State<'_, AppState>, desktop: i64, id: String, desktop_name: Option<String>) -> Vec<TodoItem> {
+    let mut data = state.data.lock().unwrap();
+    let now = chrono::Local::now();
+    let Some(todos) = data.notes.get_mut(&desktop) else {
+        return Vec::new();
+    };
+    let text = match todos.iter_mut().find(|t| t.id == id) {
+        Some(todo) => {
+            match &todo.recurrence {
+                Some(rule) => {
+                    let prev = todo
+                        .due
+                        .as_deref()
+                        .and_then(|d| chrono::DateTime::parse_from_rfc3339(d).ok())
+                        .map(|d| d.with_timezone(&chrono::Local))
+                        .unwrap_or(now);
+                    let start = *todo.dtstart.get_or_insert(prev.naive_local());
+                    todo.due = rule.next_after(start, &prev, &now).map(|d| d.to_rfc3339());
+                    todo.done = todo.due.is_none();
+                }
+                None => todo.done = true,
+            }
+            todo.text.clone()
+        }
+        None => return todos.clone(),
+    };
+    let todos = todos.clone();
+    push_completed(&mut data, text, desktop, desktop_name);
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    todos
+}
+
+/// Validate an RRULE string and return its next `count` occurrences from now.
+#[tauri::command]
+fn preview_recurrence(rule: String, count: usize) -> Result<Vec<String>, String> {
+    let rule = Recurrence::parse(&rule)?;
+    Ok(rule
+        .upcoming(&chrono::Local::now(), count)
+        .iter()
+        .map(|d| d.to_rfc3339())
+        .collect())
+}
+
+/// Delete a completed item, live or archived.
+#[tauri::command]
+fn delete_completed(state: tauri::State<'_, AppState>, id: String) -> Result<bool, String> {
+    let mut data = state.data.lock().unwrap();
+    let path = state.data_path.lock().unwrap();
+    let before = data.completed.len();
+    data.completed.retain(|c| c.id != id);
+    if data.completed.len() == before {
+        return completed::update_archived(&completed::archive_dir(&path), &id, |items, index| {
+            items.remove(index);
+        })
+        .map(|found| found.is_some());
+    }
+    persist_data(&path, &data);
+    Ok(true)
+}
+
+/// Edit a completed item's text, live or archived.
+#[tauri::command]
+fn edit_completed(state: tauri::State<'_, AppState>, id: String, text: String) -> Result<bool, String> {
+    let mut data = state.data.lock().unwrap();
+    let path = state.data_path.lock().unwrap();
+    match data.completed.iter_mut().find(|c| c.id == id) {
+        Some(item) => item.text = text,
+        None => {
+            return completed::update_archived(&completed::archive_dir(&path), &id, |items, index| items[index].text = text)
+                .map(|found| found.is_some());
+        }
+    }
+    persist_data(&path, &data);
+    Ok(true)
+}
+
+/// Move a completed item back to an open todo. It goes to its original
+/// desktop if that space still exists, otherwise to `fallback_desktop`.
+/// Returns the desktop it was restored to.
+#[tauri::command]
+fn restore_completed(state: tauri::State<'_, AppState>, id: String, fallback_desktop: Option<i64>) -> Result<i64, String> {
+    let desktops = current_desktops(&state);
+    let mut data = state.data.lock().unwrap();
+    let path = state.data_path.lock().unwrap();
+    let choose_desktop = |original: i64| {
+        if desktops.iter().any(|d| d.id == original) {
+            return Ok(original);
+        }
+        match fallback_desktop {
+            Some(fallback) if desktops.iter().any(|d| d.id == fallback) => Ok(fallback),
+            Some(fallback) => Err(format!("Desktop {} does not exist", fallback)),
+            None => Err("Original desktop no longer exists; choose a desktop to restore to".to_string()),
+        }
+    };
+
+    let (item, desktop) = match data.completed.iter().position(|c| c.id == id) {
+        Some(index) => {
+            let desktop = choose_desktop(data.completed[index].desktop_id)?;
+            (data.completed.remove(index), desktop)
+        }
+        None => {
+            // Look in the archive; the month is only rewritten once the
+            // target desktop is known.
+            let mut target = Err(format!("Completed item {} not found", id));
+            let removed = completed::update_archived(&completed::archive_dir(&path), &id, |items, index| {
+                target = choose_desktop(items[index].desktop_id);
+                match target {
+                    Ok(_) => Some(items.remove(index)),
+                    Err(_) => None,
+                }
+            })?;
+            let desktop = target?;
+            (removed.flatten().ok_or_else(|| format!("Completed item {} not found", id))?, desktop)
+        }
     };
-    data.completed.push(item);
+
+    data.notes.entry(desktop).or_default().push(TodoItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        text: item.text,
+        done: false,
+        pinned: false,
+        recurrence: None,
+        due: None,
+        dtstart: None,
+    });
+    persist_data(&path, &data);
+    Ok(desktop)
+}
+
+/// Query live and archived completed items together.
+#[tauri::command]
+fn query_completed(state: tauri::State<'_, AppState>, query: completed::CompletedQuery) -> Result<completed::CompletedPage, String> {
+    let data = state.data.lock().unwrap();
+    let path = state.data_path.lock().unwrap();
+    let month = |b: &Option<String>| b.as_deref().and_then(completed::parse_timestamp).map(|d| d.format("%Y-%m").to_string());
+    let mut items = completed::load_archived(&completed::archive_dir(&path), month(&query.from).as_deref(), month(&query.to).as_deref());
+    items.extend(data.completed.iter().cloned());
+    completed::query(&items, &query)
+}
+
+#[tauri::command]
+fn save_completed_retention(state: tauri::State<'_, AppState>, days: Option<u32>) {
+    let mut data = state.data.lock().unwrap();
+    data.settings.completed_retention_days = days;
     let path = state.data_path.lock().unwrap();
+    archive_expired_completed(&path, &mut data);
     persist_data(&path, &data);
+    drop(path); drop(data);
+    emit_settings_changed(&state);
+}
+
+// ── Completed-item archive ─────────────────────────────────────
+
+/// Move completed items older than the retention window into monthly
+/// archive files. Returns whether anything moved.
+fn archive_expired_completed(data_path: &str, data: &mut PersistData) -> bool {
+    let Some(days) = data.settings.completed_retention_days else {
+        return false;
+    };
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+    completed::archive_expired(&completed::archive_dir(data_path), &mut data.completed, cutoff)
+}
+
+fn enforce_completed_retention(handle: &tauri::AppHandle) {
+    let state = handle.state::<AppState>();
+    let mut data = state.data.lock().unwrap();
+    let path = state.data_path.lock().unwrap();
+    if archive_expired_completed(&path, &mut data) {
+        persist_data(&path, &data);
+    }
 }
 
+/// Clear the completed log, archived months included.
 #[tauri::command]
 fn clear_completed(state: tauri::State<'_, AppState>) {
     let mut data = state.data.lock().unwrap();
     data.completed.clear();
     let path = state.data_path.lock().unwrap();
     persist_data(&path, &data);
+    let dir = completed::archive_dir(&path);
+    if let Err(e) = fs::remove_dir_all(&dir) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::error!("[completed] failed to remove {}: {}", dir.display(), e);
+        }
+    }
 }
 
 // ── CFRunLoop (for background observer thread) ────────────────
@@ -1040,33 +3023,16 @@ extern "C" {
 // ── NSWorkspace space-change observer ─────────────────────────
 
 /// Returns one DesktopInfo per display (indexed by display number),
-/// matching the same logic as the `get_desktop` command.
-fn build_desktop_infos(state: &AppState) -> Vec<DesktopInfo> {
-    let spaces = enumerate_spaces();
-    let display_count = spaces.iter().map(|&(_, d, _, _)| d).max().map_or(1, |m| m + 1);
+/// matching the same logic as the `get_desktop` command. Displays whose
+/// space isn't a known desktop yet are `None`.
+fn build_desktop_infos(state: &AppState, desktops: &[Desktop]) -> Vec<Option<DesktopInfo>> {
+    let display_count = desktops.iter().map(|d| d.space.display).max().map_or(1, |m| m + 1);
     let data = state.data.lock().unwrap();
 
     (0..display_count)
         .map(|disp| {
             let (sid, _space_type) = space_info_for_display(disp);
-            let found = spaces
-                .iter()
-                .position(|&(s, _, _, _)| s == sid);
-            let position = found.unwrap_or(0) as u32;
-            let is_fullscreen = found.map(|pos| spaces[pos].3).unwrap_or(false);
-            let color = data
-                .settings
-                .custom_colors
-                .get(&sid)
-                .cloned()
-                .unwrap_or_else(|| default_color(position));
-            DesktopInfo {
-                space_id: sid,
-                position,
-                name: format!("Desktop {}", position + 1),
-                color,
-                is_fullscreen,
-            }
+            desktop_info(&data.settings, &data.desktops, desktops, sid)
         })
         .collect()
 }
@@ -1084,13 +3050,22 @@ fn start_space_observer(app_handle: tauri::AppHandle) {
 
             let handle = app_handle.clone();
             let block = RcBlock::new(move |_notification: std::ptr::NonNull<objc2_foundation::NSNotification>| {
+                notify_space_changed();
                 let state = handle.state::<AppState>();
-                let infos = build_desktop_infos(&state);
+                // Enumerate once; every consumer below reads the same snapshot
+                let desktops = current_desktops(&state);
+                let infos = build_desktop_infos(&state, &desktops);
                 if let Err(e) = handle.emit("desktop-changed", &infos) {
                     log::error!("Failed to emit desktop-changed: {}", e);
                 }
+                check_topology(&handle, &desktops);
+                record_visits(&handle, &desktops);
+                track_app_usage(&handle, &desktops);
             });
 
+            // Seed history with the desktops active at launch.
+            record_visits(&app_handle, &current_desktops(&app_handle.state::<AppState>()));
+
             let _observer = nc.addObserverForName_object_queue_usingBlock(
                 Some(&name),
                 None,
@@ -1105,6 +3080,278 @@ fn start_space_observer(app_handle: tauri::AppHandle) {
     });
 }
 
+// ── App usage tracking ────────────────────────────────────────
+
+const DEFAULT_DESKTOP_APPS_LIMIT: usize = 10;
+
+static USAGE_TRACKER: Mutex<UsageTracker> = Mutex::new(UsageTracker::new());
+
+/// Usage recorded since the data was last written. Activations only update
+/// memory; the checkpoint thread and app exit write it out.
+static USAGE_UNSAVED: Mutex<bool> = Mutex::new(false);
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventSourceSecondsSinceLastEventType(state: i32, event_type: u32) -> f64;
+}
+
+const K_CG_EVENT_SOURCE_STATE_COMBINED_SESSION: i32 = 0;
+const K_CG_ANY_INPUT_EVENT_TYPE: u32 = !0;
+
+/// Seconds since the last keyboard or mouse input.
+fn idle_seconds() -> i64 {
+    unsafe { CGEventSourceSecondsSinceLastEventType(K_CG_EVENT_SOURCE_STATE_COMBINED_SESSION, K_CG_ANY_INPUT_EVENT_TYPE) as i64 }
+}
+
+/// Fold a finished segment into the in-memory totals.
+fn record_usage(state: &AppState, finished: Option<(usage::Segment, u64)>, now: chrono::DateTime<chrono::Utc>) {
+    let Some((segment, secs)) = finished else {
+        return;
+    };
+    let mut data = state.data.lock().unwrap();
+    if usage::record(data.app_usage.entry(segment.desktop).or_default(), &segment, secs, now) {
+        *USAGE_UNSAVED.lock().unwrap() = true;
+    }
+}
+
+/// Write the data file if usage changed since it was last written.
+fn flush_app_usage(state: &AppState) {
+    if !std::mem::take(&mut *USAGE_UNSAVED.lock().unwrap()) {
+        return;
+    }
+    let data = state.data.lock().unwrap();
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+}
+
+/// (bundle id, name) of the frontmost app.
+fn frontmost_app() -> Option<(String, String)> {
+    use objc2_app_kit::NSWorkspace;
+    let app = NSWorkspace::sharedWorkspace().frontmostApplication()?;
+    let bundle_id = app.bundleIdentifier()?.to_string();
+    let name = app.localizedName().map_or_else(|| bundle_id.clone(), |n| n.to_string());
+    Some((bundle_id, name))
+}
+
+/// Close the running usage segment and start one for the current frontmost
+/// app on the focused desktop. Time spent in this app itself isn't counted.
+fn track_app_usage(handle: &tauri::AppHandle, desktops: &[Desktop]) {
+    let state = handle.state::<AppState>();
+    let active_sid = unsafe { CGSGetActiveSpace(CGSMainConnectionID()) };
+    let desktop = desktops.iter().find(|d| d.space.space_id == active_sid);
+    let next = match (desktop, frontmost_app()) {
+        (Some(desktop), Some((bundle_id, name))) if bundle_id != handle.config().identifier => {
+            Some((desktop.id, bundle_id, name))
+        }
+        _ => None,
+    };
+
+    let now = chrono::Utc::now();
+    let finished = USAGE_TRACKER.lock().unwrap().transition(next, now);
+    record_usage(&state, finished, now);
+}
+
+/// Cut the running segment so long sessions are counted as they go and
+/// idle time is left out, then write out what's been recorded.
+fn checkpoint_app_usage(handle: &tauri::AppHandle) {
+    let state = handle.state::<AppState>();
+    let now = chrono::Utc::now();
+    let finished = USAGE_TRACKER.lock().unwrap().checkpoint(now, idle_seconds());
+    record_usage(&state, finished, now);
+    flush_app_usage(&state);
+}
+
+/// Close the running segment and write everything out before quitting.
+fn finish_app_usage(handle: &tauri::AppHandle) {
+    let state = handle.state::<AppState>();
+    let now = chrono::Utc::now();
+    let finished = USAGE_TRACKER.lock().unwrap().transition(None, now);
+    record_usage(&state, finished, now);
+    flush_app_usage(&state);
+}
+
+/// Most-used apps on `desktop`, by time spent frontmost.
+#[tauri::command]
+fn get_desktop_apps(state: tauri::State<'_, AppState>, desktop: i64, limit: Option<usize>) -> Vec<AppUsage> {
+    let data = state.data.lock().unwrap();
+    data.app_usage
+        .get(&desktop)
+        .map(|apps| usage::top_apps(apps, limit.unwrap_or(DEFAULT_DESKTOP_APPS_LIMIT)))
+        .unwrap_or_default()
+}
+
+fn start_app_usage_observer(app_handle: tauri::AppHandle) {
+    let checkpoint_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(usage::CHECKPOINT_SECS));
+        checkpoint_app_usage(&checkpoint_handle);
+    });
+
+    std::thread::spawn(move || {
+        unsafe {
+            use objc2_app_kit::NSWorkspace;
+            use block2::RcBlock;
+
+            let workspace = NSWorkspace::sharedWorkspace();
+            let nc = workspace.notificationCenter();
+
+            let name = objc2_foundation::NSString::from_str("NSWorkspaceDidActivateApplicationNotification");
+
+            let handle = app_handle.clone();
+            let block = RcBlock::new(move |_notification: std::ptr::NonNull<objc2_foundation::NSNotification>| {
+                track_app_usage(&handle, &current_desktops(&handle.state::<AppState>()));
+            });
+
+            track_app_usage(&app_handle, &current_desktops(&app_handle.state::<AppState>()));
+
+            let _observer = nc.addObserverForName_object_queue_usingBlock(
+                Some(&name),
+                None,
+                None,
+                &block,
+            );
+
+            CFRunLoopRun();
+        }
+    });
+}
+
+// ── Space topology watcher ────────────────────────────────────
+
+// Adding, removing or dragging desktops in Mission Control doesn't post a
+// workspace notification, so the topology is also polled.
+const TOPOLOGY_POLL_INTERVAL_MS: u64 = 2000;
+
+/// Last topology seen, for diffing.
+static LAST_TOPOLOGY: Mutex<Option<Vec<SpacePosition>>> = Mutex::new(None);
+
+fn topology_snapshot(desktops: &[Desktop]) -> Vec<SpacePosition> {
+    desktops
+        .iter()
+        .enumerate()
+        .map(|(pos, d)| SpacePosition {
+            desktop_id: d.id,
+            display: d.space.display,
+            position: pos as u32,
+            local: d.space.local,
+        })
+        .collect()
+}
+
+/// Diff the current topology against the last snapshot and emit
+/// `spaces-added`, `spaces-removed` and `spaces-reordered`.
+fn check_topology(handle: &tauri::AppHandle, desktops: &[Desktop]) {
+    let state = handle.state::<AppState>();
+    let snapshot = topology_snapshot(desktops);
+    if snapshot.is_empty() {
+        // CGS lookup failed; don't report every desktop as removed
+        return;
+    }
+    let previous = match LAST_TOPOLOGY.lock().unwrap().replace(snapshot.clone()) {
+        Some(previous) => previous,
+        None => return,
+    };
+
+    let diff = spaces::diff_topology(&previous, &snapshot);
+    if diff.is_empty() {
+        return;
+    }
+    log::info!(
+        "[spaces] topology changed: {} added, {} removed, {} reordered",
+        diff.added.len(), diff.removed.len(), diff.reordered.len()
+    );
+
+    if !diff.added.is_empty() {
+        if let Err(e) = handle.emit("spaces-added", &diff.added) {
+            log::error!("Failed to emit spaces-added: {}", e);
+        }
+    }
+    if !diff.removed.is_empty() {
+        if let Err(e) = handle.emit("spaces-removed", &diff.removed) {
+            log::error!("Failed to emit spaces-removed: {}", e);
+        }
+    }
+    if !diff.reordered.is_empty() {
+        if let Err(e) = handle.emit("spaces-reordered", &diff.reordered) {
+            log::error!("Failed to emit spaces-reordered: {}", e);
+        }
+    }
+
+    // Position-based names and default colors may have shifted
+    let infos = build_desktop_infos(&state, desktops);
+    if let Err(e) = handle.emit("desktop-changed", &infos) {
+        log::error!("Failed to emit desktop-changed: {}", e);
+    }
+}
+
+fn start_topology_watcher(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        check_topology(&app_handle, &current_desktops(&app_handle.state::<AppState>()));
+        std::thread::sleep(std::time::Duration::from_millis(TOPOLOGY_POLL_INTERVAL_MS));
+    });
+}
+
+// ── Daily session rollover ────────────────────────────────────
+
+const ROLLOVER_CHECK_INTERVAL_SECS: u64 = 60;
+
+fn parse_rollover_time(time: &str) -> Result<chrono::NaiveTime, String> {
+    chrono::NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| format!("Invalid rollover time '{}', expected HH:MM", time))
+}
+
+fn check_rollover(handle: &tauri::AppHandle) {
+    let state = handle.state::<AppState>();
+    let mut data = state.data.lock().unwrap();
+    let at = match data.settings.rollover_time.as_deref().map(parse_rollover_time) {
+        Some(Ok(at)) => at,
+        _ => return,
+    };
+    let now = chrono::Local::now();
+    let last = data
+        .last_rollover
+        .as_deref()
+        .and_then(|l| chrono::DateTime::parse_from_rfc3339(l).ok())
+        .map(|l| l.with_timezone(&chrono::Local));
+    let last = match last {
+        Some(last) => last,
+        None => {
+            // Start counting from now, so the first rollover is the next
+            // scheduled time rather than one that has already passed
+            data.last_rollover = Some(now.to_rfc3339());
+            let path = state.data_path.lock().unwrap();
+            persist_data(&path, &data);
+            return;
+        }
+    };
+    if !recurrence::daily_due(&now, at, &last) {
+        return;
+    }
+
+    let carry = data.settings.rollover_carry;
+    let summary = archive_session(&mut data, carry);
+    data.last_rollover = Some(now.to_rfc3339());
+    let path = state.data_path.lock().unwrap();
+    persist_data(&path, &data);
+    drop(path); drop(data);
+
+    log::info!("[rollover] started new session ({} desktop(s) archived)", summary.archived_desktops);
+    if let Err(e) = handle.emit("session-rollover", &summary) {
+        log::error!("Failed to emit session-rollover: {}", e);
+    }
+}
+
+/// Poll for the configured rollover time. A sleeping Mac doesn't tick, so the
+/// first check after wake picks up a rollover that came due overnight.
+/// Completed-item retention rides on the same tick.
+fn start_rollover_scheduler(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        check_rollover(&app_handle);
+        enforce_completed_retention(&app_handle);
+        std::thread::sleep(std::time::Duration::from_secs(ROLLOVER_CHECK_INTERVAL_SECS));
+    });
+}
+
 // ── Monitor connect/disconnect observer ───────────────────────
 
 // Flags from CGDisplayChangeSummaryFlags
@@ -1284,6 +3531,11 @@ pub fn run() {
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .register_asynchronous_uri_scheme_protocol("appicon", |ctx, request, responder| {
+            let app = ctx.app_handle().clone();
+            std::thread::spawn(move || responder.respond(serve_app_icon(&app, &request)));
+        })
         .setup(|app| {
             // Persistence setup — use a separate file in debug builds
             // so dev and prod don't clobber each other's data.
@@ -1302,12 +3554,56 @@ pub fn run() {
                 persist_data(&data_path_str, &data);
             }
 
+            // Migrate v1 → v2 (space id64 → stable desktop id)
+            if data.version < 2 {
+                log::info!("Migrating data from v1 to v2");
+                migrate_v1_to_v2(&mut data);
+                persist_data(&data_path_str, &data);
+            }
+
+            // Migrate v2 → v3 (shell launch commands → argv templates)
+            if data.version < 3 {
+                log::info!("Migrating data from v2 to v3");
+                migrate_v2_to_v3(&mut data);
+                persist_data(&data_path_str, &data);
+            }
+
+            // Migrate v3 → v4 (common apps keyed by bundle id)
+            if data.version < 4 {
+                log::info!("Migrating data from v3 to v4");
+                migrate_v3_to_v4(&mut data);
+                persist_data(&data_path_str, &data);
+            }
+
+            // Migrate v4 → v5 (explicit common app order)
+            if data.version < 5 {
+                log::info!("Migrating data from v4 to v5");
+                migrate_v4_to_v5(&mut data);
+                persist_data(&data_path_str, &data);
+            }
+
+            let hotkey_bindings = data.settings.hotkeys.clone();
             app.manage(AppState {
                 data: Mutex::new(data),
                 data_path: Mutex::new(data_path_str),
                 app_handle: app.handle().clone(),
             });
 
+            // Register global hotkeys; failures are kept for the settings UI
+            register_hotkeys(app.handle(), &hotkey_bindings);
+
+            // Continuing a session arms every desktop's app set for its next visit
+            let session_handle = app.handle().clone();
+            app.listen_any("session-action", move |event| {
+                let continued = serde_json::from_str::<serde_json::Value>(event.payload())
+                    .is_ok_and(|v| v["action"] == "continue");
+                let state = session_handle.state::<AppState>();
+                let data = state.data.lock().unwrap();
+                if continued && data.settings.launch_apps_on_restore {
+                    arm_app_launch(data.app_sets.keys().copied());
+                }
+            });
+
             // Hide from Dock — pure menu-bar app
             #[cfg(target_os = "macos")]
             app.set_activation_policy(tauri::ActivationPolicy::Accessory);
@@ -1330,6 +3626,8 @@ pub fn run() {
                 &quit,
             ])?;
 
+            app.manage(TrayItems { toggle_all: toggle_all.clone() });
+
             // Clones for the on_tray_icon_event closure to refresh labels
             let tray_toggle_all = toggle_all.clone();
             let tray_toggle_desktop = toggle_desktop.clone();
@@ -1359,7 +3657,7 @@ pub fn run() {
                             tray_toggle_all.set_text(if any_visible { "Hide Entirely" } else { "Show Entirely" }).ok();
 
                             let spaces = enumerate_spaces();
-                            let display_count = spaces.iter().map(|&(_, d, _, _)| d).max().map_or(1, |m| m + 1);
+                            let display_count = spaces.iter().map(|s| s.display).max().map_or(1, |m| m + 1);
                             let mut active_space_per_display: HashMap<usize, i64> = HashMap::new();
                             for disp in 0..display_count {
                                 let (sid, _) = space_info_for_display(disp);
@@ -1384,24 +3682,11 @@ pub fn run() {
                 .on_menu_event(move |app, event| {
                     match event.id.as_ref() {
                         "toggle_all" => {
-                            let windows = app.webview_windows();
-                            let any_visible = windows.values().any(|w| w.is_visible().unwrap_or(false));
-                            if any_visible {
-                                for window in windows.values() {
-                                    window.hide().ok();
-                                }
-                                toggle_all.set_text("Show Entirely").ok();
-                            } else {
-                                for window in windows.values() {
-                                    window.show().ok();
-                                    window.set_focus().ok();
-                                }
-                                toggle_all.set_text("Hide Entirely").ok();
-                            }
+                            toggle_all_windows(app);
                         }
                         "toggle_desktop" => {
                             let spaces = enumerate_spaces();
-                            let display_count = spaces.iter().map(|&(_, d, _, _)| d).max().map_or(1, |m| m + 1);
+                            let display_count = spaces.iter().map(|s| s.display).max().map_or(1, |m| m + 1);
 
                             let mut active_space_per_display: HashMap<usize, i64> = HashMap::new();
                             for disp in 0..display_count {
@@ -1544,12 +3829,117 @@ pub fn run() {
             // Start NSWorkspace observer for space changes
             start_space_observer(app.handle().clone());
 
+            // Track which apps are used on which desktop
+            start_app_usage_observer(app.handle().clone());
+
+            // Poll for desktops being added, removed or reordered
+            start_topology_watcher(app.handle().clone());
+
             // Start monitor connect/disconnect observer
             start_monitor_observer(app.handle().clone());
 
+            // Start daily session rollover
+            start_rollover_scheduler(app.handle().clone());
+
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![get_desktop, get_todos, save_todos, get_title, save_title, list_all_desktops, list_desktops_grouped, switch_desktop, get_settings, complete_setup, save_color, list_all_spaces, check_accessibility, request_accessibility, save_desktop_count, apply_theme, clear_all_data, start_new_session, get_context_history, restore_context, save_timer_presets, save_notify_settings, save_hidden_panels, save_auto_hide_delay, get_common_apps, save_common_apps, get_dismissed_tips, save_dismissed_tips, list_installed_apps, launch_app, launch_app_new, open_new_app_instance, add_common_app, remove_common_app, get_completed, add_completed, clear_completed])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(tauri::generate_handler![get_desktop, get_todos, save_todos, get_title, save_title, get_title_pinned, save_title_pinned, list_all_desktops, list_desktops_grouped, switch_desktop, switch_to_desktop_by_query, get_navigation_history, go_back, go_forward, toggle_last_desktop, get_settings, complete_setup, save_color, save_desktop_name, list_all_spaces, check_accessibility, request_accessibility, save_desktop_count, apply_theme, save_fullscreen_settings, save_hotkeys, get_hotkey_errors, clear_all_data, list_orphaned_desktops, reassign_desktop_data, purge_orphans, start_new_session, get_context_history, restore_context, save_timer_presets, save_notify_settings, save_hidden_panels, save_auto_hide_delay, save_rollover_settings, get_common_apps, save_common_apps, get_dismissed_tips, save_dismissed_tips, list_installed_apps, launch_app, launch_app_new, open_new_app_instance, get_launch_log, clear_launch_log, validate_launch_command, get_launch_recipes, add_common_app, remove_common_app, reorder_common_apps, save_app_groups, launch_common_app, check_common_apps, get_desktop_app_set, save_desktop_app_set, save_launch_apps_on_restore, launch_desktop_apps, get_bookmarks, add_bookmark, remove_bookmark, open_bookmark, get_desktop_apps, get_completed, add_completed, complete_todo, preview_recurrence, delete_completed, edit_completed, restore_completed, query_completed, save_completed_retention, clear_completed])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                finish_app_usage(app);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn todo(id: &str, done: bool, pinned: bool) -> TodoItem {
+        TodoItem {
+            id: id.into(),
+            text: id.into(),
+            done,
+            pinned,
+            recurrence: None,
+            due: None,
+            dtstart: None,
+        }
+    }
+
+    /// Desktop 1 has a titled, pinned session; desktop 2 an unpinned one.
+    fn session() -> PersistData {
+        let mut data = PersistData::default();
+        data.notes.insert(1, vec![todo("pinned-done", true, true), todo("open", false, false), todo("done", true, false)]);
+        data.notes.insert(2, vec![todo("other", false, false)]);
+        data.titles.insert(1, "Pinned".into());
+        data.titles.insert(2, "Unpinned".into());
+        data.pinned_titles.insert(1);
+        data
+    }
+
+    fn ids(data: &PersistData, desktop: i64) -> Vec<&str> {
+        data.notes.get(&desktop).map(|t| t.iter().map(|t| t.id.as_str()).collect()).unwrap_or_default()
+    }
+
+    #[test]
+    fn archive_session_archives_everything_to_history() {
+        for carry in [CarryMode::All, CarryMode::Pinned, CarryMode::None] {
+            let mut data = session();
+            let summary = archive_session(&mut data, carry);
+            assert_eq!(summary.archived_desktops, 2);
+            assert_eq!(summary.done_count, 2);
+            assert_eq!(summary.open_count, 2);
+            let saved = &data.context_history[&1][0];
+            assert_eq!(saved.title, "Pinned");
+            assert_eq!(saved.todos.len(), 3);
+            assert_eq!(data.context_history[&2][0].title, "Unpinned");
+        }
+    }
+
+    #[test]
+    fn archive_session_carry_pinned_keeps_only_pinned() {
+        let mut data = session();
+        let summary = archive_session(&mut data, CarryMode::Pinned);
+        assert_eq!(ids(&data, 1), ["pinned-done"]);
+        assert!(!data.notes[&1][0].done);
+        assert!(!data.notes.contains_key(&2));
+        assert_eq!(summary.carried_count, 1);
+        assert_eq!(data.titles.get(&1).map(String::as_str), Some("Pinned"));
+        assert!(!data.titles.contains_key(&2));
+    }
+
+    #[test]
+    fn archive_session_carry_all_keeps_pinned_and_unfinished() {
+        let mut data = session();
+        let summary = archive_session(&mut data, CarryMode::All);
+        assert_eq!(ids(&data, 1), ["pinned-done", "open"]);
+        assert!(data.notes[&1].iter().all(|t| !t.done));
+        assert_eq!(ids(&data, 2), ["other"]);
+        assert_eq!(summary.carried_count, 3);
+        assert_eq!(data.titles.len(), 2);
+    }
+
+    #[test]
+    fn archive_session_carry_none_clears_everything() {
+        let mut data = session();
+        let summary = archive_session(&mut data, CarryMode::None);
+        assert!(data.notes.is_empty());
+        assert!(data.titles.is_empty());
+        assert_eq!(summary.carried_count, 0);
+        // Pins are kept for the next session
+        assert!(data.pinned_titles.contains(&1));
+    }
+
+    #[test]
+    fn archive_session_caps_history() {
+        let mut data = session();
+        for _ in 0..MAX_HISTORY_PER_DESKTOP + 5 {
+            data.titles.insert(2, "Unpinned".into());
+            archive_session(&mut data, CarryMode::Pinned);
+        }
+        assert_eq!(data.context_history[&2].len(), MAX_HISTORY_PER_DESKTOP);
+    }
 }
@@ -0,0 +1,194 @@
+//! Known ways to open a new window of an app, keyed by bundle id.
+//!
+//! Not every app opens a new window with `open -n`. The built-in registry
+//! records what works for common apps; users can add or replace recipes in
+//! an overrides file, which wins over the built-ins for the same bundle id.
+
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever the built-in recipes change. Overrides files carry the
+/// version they were written for.
+pub const REGISTRY_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum Strategy {
+    /// `open -n` works.
+    OpenNew,
+    /// A launch template, as in `CommonApp::launch_args`.
+    Command {
+        command: String,
+        #[serde(default)]
+        raw_shell: bool,
+    },
+    /// No known way to get a new window.
+    Unsupported,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Recipe {
+    pub bundle_id: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub strategy: Strategy,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Contents of the overrides file.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RecipeFile {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub recipes: Vec<Recipe>,
+}
+
+fn recipe(bundle_id: &str, name: &str, strategy: Strategy, note: Option<&str>) -> Recipe {
+    Recipe { bundle_id: bundle_id.to_string(), name: name.to_string(), strategy, note: note.map(str::to_string) }
+}
+
+fn command(command: &str) -> Strategy {
+    Strategy::Command { command: command.to_string(), raw_shell: false }
+}
+
+pub fn builtin() -> Vec<Recipe> {
+    use Strategy::{OpenNew, Unsupported};
+    vec![
+        recipe("com.figma.Desktop", "Figma", Unsupported, None),
+        recipe("org.mozilla.firefox", "Firefox", OpenNew, None),
+        recipe(
+            "com.google.Chrome",
+            "Google Chrome",
+            command(r#"osascript -e 'tell application "Google Chrome" to make new window'"#),
+            None,
+        ),
+        recipe("com.googlecode.iterm2", "iTerm2", OpenNew, None),
+        recipe("com.linear", "Linear", OpenNew, None),
+        recipe("com.microsoft.edgemac", "Microsoft Edge", OpenNew, None),
+        recipe("notion.id", "Notion", Unsupported, None),
+        recipe("md.obsidian", "Obsidian", Unsupported, None),
+        recipe("at.eggerapps.Postico2", "Postico", OpenNew, None),
+        recipe("com.postmanlabs.mac", "Postman", Unsupported, None),
+        recipe("com.apple.Safari", "Safari", OpenNew, None),
+        recipe("com.tinyspeck.slackmacgap", "Slack", Unsupported, None),
+        recipe("com.microsoft.VSCode", "VS Code", command("code --new-window"), Some("Requires the `code` CLI tool")),
+        recipe("net.whatsapp.WhatsApp", "WhatsApp", Unsupported, None),
+    ]
+}
+
+/// Parse an overrides file. Files written for a newer registry are still
+/// read, since the recipe format only gains optional fields.
+pub fn parse_overrides(text: &str) -> Result<Vec<Recipe>, String> {
+    let file: RecipeFile = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    for recipe in &file.recipes {
+        if let Strategy::Command { command, raw_shell } = &recipe.strategy {
+            crate::launch::validate(command, *raw_shell).map_err(|e| format!("{}: {}", recipe.bundle_id, e))?;
+        }
+    }
+    Ok(file.recipes)
+}
+
+/// Built-ins with `overrides` applied, sorted by name. Bundle ids compare
+/// case-insensitively.
+pub fn merged(overrides: &[Recipe]) -> Vec<Recipe> {
+    let mut recipes: Vec<Recipe> = builtin()
+        .into_iter()
+        .filter(|b| !overrides.iter().any(|o| o.bundle_id.eq_ignore_ascii_case(&b.bundle_id)))
+        .collect();
+    recipes.extend(overrides.iter().cloned());
+    recipes.sort_by_key(|r| r.name.to_lowercase());
+    recipes
+}
+
+pub fn find<'a>(recipes: &'a [Recipe], bundle_id: &str) -> Option<&'a Recipe> {
+    recipes.iter().find(|r| r.bundle_id.eq_ignore_ascii_case(bundle_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_err(text: &str) -> String {
+        parse_overrides(text).unwrap_err()
+    }
+
+    #[test]
+    fn parse_overrides_reads_each_strategy() {
+        let recipes = parse_overrides(
+            r#"{"version": 1, "recipes": [
+                {"bundle_id": "a", "name": "A", "strategy": "open_new"},
+                {"bundle_id": "b", "name": "B", "strategy": "command", "command": "b --new", "note": "n"},
+                {"bundle_id": "c", "name": "C", "strategy": "unsupported"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(recipes[0].strategy, Strategy::OpenNew);
+        assert_eq!(recipes[1].strategy, command("b --new"));
+        assert_eq!(recipes[1].note.as_deref(), Some("n"));
+        assert_eq!(recipes[2].strategy, Strategy::Unsupported);
+    }
+
+    #[test]
+    fn parse_overrides_accepts_newer_versions_and_empty_files() {
+        assert_eq!(parse_overrides(r#"{"version": 99, "recipes": []}"#).unwrap(), vec![]);
+        assert_eq!(parse_overrides("{}").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn parse_overrides_rejects_malformed_entries() {
+        assert!(parse_err("not json").contains("line 1"));
+        assert!(parse_err(r#"{"recipes": [{"name": "A", "strategy": "open_new"}]}"#).contains("bundle_id"));
+        assert!(parse_err(r#"{"recipes": [{"bundle_id": "a", "name": "A"}]}"#).contains("strategy"));
+        assert!(parse_err(r#"{"recipes": [{"bundle_id": "a", "name": "A", "strategy": "teleport"}]}"#).contains("teleport"));
+        assert!(parse_err(r#"{"recipes": [{"bundle_id": "a", "name": "A", "strategy": "command"}]}"#).contains("command"));
+    }
+
+    #[test]
+    fn parse_overrides_names_the_recipe_with_a_bad_command() {
+        let err = parse_err(r#"{"recipes": [{"bundle_id": "com.example.app", "name": "A", "strategy": "command", "command": "a | b"}]}"#);
+        assert!(err.starts_with("com.example.app: "), "{}", err);
+        assert!(err.contains("Run through shell"), "{}", err);
+        // The same command is fine once it runs through the shell
+        let text = r#"{"recipes": [{"bundle_id": "a", "name": "A", "strategy": "command", "command": "a | b", "raw_shell": true}]}"#;
+        assert!(parse_overrides(text).is_ok());
+    }
+
+    #[test]
+    fn builtin_commands_are_valid() {
+        for recipe in builtin() {
+            if let Strategy::Command { command, raw_shell } = &recipe.strategy {
+                assert!(crate::launch::validate(command, *raw_shell).is_ok(), "{}", recipe.name);
+            }
+        }
+    }
+
+    #[test]
+    fn merged_prefers_overrides_over_builtins() {
+        let overrides = vec![
+            recipe("COM.APPLE.SAFARI", "Safari", Strategy::Unsupported, Some("mine")),
+            recipe("com.example.new", "Another", Strategy::OpenNew, None),
+        ];
+        let recipes = merged(&overrides);
+        assert_eq!(recipes.len(), builtin().len() + 1);
+        let safari: Vec<&Recipe> = recipes.iter().filter(|r| r.bundle_id.eq_ignore_ascii_case("com.apple.Safari")).collect();
+        assert_eq!(safari, [&overrides[0]]);
+        assert!(recipes.contains(&overrides[1]));
+    }
+
+    #[test]
+    fn merged_sorts_by_name_ignoring_case() {
+        let recipes = merged(&[recipe("x", "aardvark", Strategy::OpenNew, None)]);
+        assert_eq!(recipes[0].name, "aardvark");
+        let names: Vec<String> = recipes.iter().map(|r| r.name.to_lowercase()).collect();
+        assert!(names.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn find_matches_bundle_id_ignoring_case() {
+        let recipes = builtin();
+        assert_eq!(find(&recipes, "COM.GOOGLE.CHROME").unwrap().name, "Google Chrome");
+        assert_eq!(find(&recipes, "com.google.chrome").unwrap().name, "Google Chrome");
+        assert!(find(&recipes, "com.google.chrome.canary").is_none());
+    }
+}
@@ -0,0 +1,396 @@
+//! RRULE-style recurrence for todos.
+//!
+//! Only the subset of RFC 5545 we need is supported:
+//! `FREQ=DAILY|WEEKLY`, `INTERVAL=n` and `BYDAY=MO,TU,...` (weekly only).
+//! Every occurrence is resolved from the series' start (`DTSTART`) in local
+//! wall-clock time, so a 09:00 daily task stays at 09:00 across DST changes
+//! and a time moved by a DST gap doesn't shift the occurrences after it.
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Recurrence {
+    pub freq: Frequency,
+    pub interval: u32,
+    /// Weekly only; empty means "the weekday of the series start".
+    pub by_day: Vec<Weekday>,
+}
+
+/// Upper bound on candidate days scanned when looking for the next occurrence.
+const MAX_SCAN_DAYS: i64 = 366 * 10;
+
+const WEEKDAY_CODES: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+impl Recurrence {
+    /// Parse an RRULE string such as `FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR`.
+    /// A leading `RRULE:` prefix is accepted.
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+        let mut freq = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+
+        for part in rule.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid rule part '{}'", part))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        other => return Err(format!("Unsupported frequency '{}'", other)),
+                    });
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("Invalid interval '{}'", value))?;
+                }
+                "BYDAY" => {
+                    for code in value.split(',') {
+                        let code = code.trim().to_ascii_uppercase();
+                        let day = WEEKDAY_CODES
+                            .iter()
+                            .find(|(c, _)| *c == code)
+                            .map(|&(_, d)| d)
+                            .ok_or_else(|| format!("Invalid weekday '{}'", code))?;
+                        if !by_day.contains(&day) {
+                            by_day.push(day);
+                        }
+                    }
+                }
+                other => return Err(format!("Unsupported rule part '{}'", other)),
+            }
+        }
+
+        let freq = freq.ok_or("Missing FREQ")?;
+        if freq == Frequency::Daily && !by_day.is_empty() {
+            return Err("BYDAY is only supported with FREQ=WEEKLY".to_string());
+        }
+        by_day.sort_by_key(|d| d.num_days_from_monday());
+        Ok(Self { freq, interval, by_day })
+    }
+
+    /// Whether `date` is an occurrence date for a series anchored at `anchor`.
+    fn matches(&self, anchor: NaiveDate, date: NaiveDate) -> bool {
+        let interval = self.interval as i64;
+        match self.freq {
+            Frequency::Daily => (date - anchor).num_days() % interval == 0,
+            Frequency::Weekly => {
+                let week_start = |d: NaiveDate| d - Duration::days(d.weekday().num_days_from_monday() as i64);
+                let weeks = (week_start(date) - week_start(anchor)).num_weeks();
+                let on_day = if self.by_day.is_empty() {
+                    date.weekday() == anchor.weekday()
+                } else {
+                    self.by_day.contains(&date.weekday())
+                };
+                weeks % interval == 0 && on_day
+            }
+        }
+    }
+
+    /// The first occurrence strictly after both `prev` and `now` of a series
+    /// starting at the local time `start`. Missed occurrences are skipped so
+    /// an overdue task reschedules into the future rather than into the past.
+    pub fn next_after<Tz: TimeZone>(
+        &self,
+        start: NaiveDateTime,
+        prev: &DateTime<Tz>,
+        now: &DateTime<Tz>,
+    ) -> Option<DateTime<Tz>> {
+        let tz = prev.timezone();
+        let from = prev.clone().max(now.clone()).naive_local().date().max(start.date());
+
+        (0..=MAX_SCAN_DAYS)
+            .map(|offset| from + Duration::days(offset))
+            .filter(|&date| self.matches(start.date(), date))
+            .map(|date| resolve_local(&tz, date.and_time(start.time())))
+            .find(|candidate| candidate > prev && candidate > now)
+    }
+
+    /// The next `count` occurrences after `start`, for previews in the UI.
+    pub fn upcoming<Tz: TimeZone>(&self, start: &DateTime<Tz>, count: usize) -> Vec<DateTime<Tz>> {
+        let mut out = Vec::with_capacity(count);
+        let mut prev = start.clone();
+        while out.len() < count {
+            match self.next_after(start.naive_local(), &prev, start) {
+                Some(next) => {
+                    out.push(next.clone());
+                    prev = next;
+                }
+                None => break,
+            }
+        }
+        out
+    }
+}
+
+/// Whether an event scheduled daily at local time `at` has come due since
+/// `last`. Only the most recent scheduled time counts, so waking after
+/// several days away fires once rather than once per missed day. A time in
+/// a spring-forward gap fires at the first valid minute after it.
+pub fn daily_due<Tz: TimeZone>(now: &DateTime<Tz>, at: NaiveTime, last: &DateTime<Tz>) -> bool {
+    let tz = now.timezone();
+    let today = now.naive_local().date();
+    let scheduled_on = |date: NaiveDate| resolve_local(&tz, date.and_time(at));
+    let latest = match scheduled_on(today) {
+        t if t <= *now => Some(t),
+        _ => today.pred_opt().map(scheduled_on),
+    };
+    latest.is_some_and(|t| *last < t)
+}
+
+/// Map a local wall-clock time to an instant. Times that fall in a
+/// spring-forward gap move forward to the first valid minute; ambiguous
+/// fall-back times pick the earlier instant.
+fn resolve_local<Tz: TimeZone>(tz: &Tz, naive: NaiveDateTime) -> DateTime<Tz> {
+    let mut probe = naive;
+    loop {
+        match tz.from_local_datetime(&probe) {
+            LocalResult::Single(dt) => return dt,
+            LocalResult::Ambiguous(earliest, _) => return earliest,
+            LocalResult::None => probe += Duration::minutes(1),
+        }
+    }
+}
+
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+        };
+        write!(f, "FREQ={}", freq)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self
+                .by_day
+                .iter()
+                .filter_map(|d| WEEKDAY_CODES.iter().find(|(_, w)| w == d).map(|&(c, _)| c))
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(rule: String) -> Result<Self, Self::Error> {
+        Self::parse(&rule)
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(rule: Recurrence) -> Self {
+        rule.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use chrono_tz::America::New_York;
+    use chrono_tz::Tz;
+
+    fn ny(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Tz> {
+        New_York.with_ymd_and_hms(y, m, d, h, min, 0).earliest().unwrap()
+    }
+
+    fn local_time(dt: &DateTime<Tz>) -> NaiveTime {
+        dt.naive_local().time()
+    }
+
+    #[test]
+    fn daily_keeps_wall_clock_time_across_spring_forward() {
+        // 2026-03-08 02:00 EST jumps to 03:00 EDT.
+        let rule = Recurrence::parse("FREQ=DAILY").unwrap();
+        let prev = ny(2026, 3, 7, 9, 0);
+        let next = rule.next_after(prev.naive_local(), &prev, &prev).unwrap();
+        assert_eq!(next.date_naive(), NaiveDate::from_ymd_opt(2026, 3, 8).unwrap());
+        assert_eq!(local_time(&next), NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        assert_eq!(next - prev, Duration::hours(23));
+    }
+
+    #[test]
+    fn daily_keeps_wall_clock_time_across_fall_back() {
+        // 2026-11-01 02:00 EDT falls back to 01:00 EST.
+        let rule = Recurrence::parse("FREQ=DAILY").unwrap();
+        let prev = ny(2026, 10, 31, 9, 0);
+        let next = rule.next_after(prev.naive_local(), &prev, &prev).unwrap();
+        assert_eq!(local_time(&next), NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        assert_eq!(next - prev, Duration::hours(25));
+    }
+
+    #[test]
+    fn time_in_spring_forward_gap_moves_to_first_valid_minute() {
+        let rule = Recurrence::parse("FREQ=DAILY").unwrap();
+        let prev = ny(2026, 3, 7, 2, 30);
+        let next = rule.next_after(prev.naive_local(), &prev, &prev).unwrap();
+        assert_eq!(next.naive_local(), NaiveDate::from_ymd_opt(2026, 3, 8).unwrap().and_hms_opt(3, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn time_moved_by_gap_returns_to_start_time() {
+        let rule = Recurrence::parse("FREQ=DAILY").unwrap();
+        let start = ny(2026, 3, 7, 2, 30);
+        let dtstart = start.naive_local();
+        let gap = rule.next_after(dtstart, &start, &start).unwrap();
+        assert_eq!(gap.naive_local(), NaiveDate::from_ymd_opt(2026, 3, 8).unwrap().and_hms_opt(3, 0, 0).unwrap());
+        let after = rule.next_after(dtstart, &gap, &gap).unwrap();
+        assert_eq!(after.naive_local(), NaiveDate::from_ymd_opt(2026, 3, 9).unwrap().and_hms_opt(2, 30, 0).unwrap());
+
+        let dates: Vec<_> = rule.upcoming(&start, 3).iter().map(local_time).collect();
+        let half_past = |h| NaiveTime::from_hms_opt(h, 30, 0).unwrap();
+        assert_eq!(dates, vec![NaiveTime::from_hms_opt(3, 0, 0).unwrap(), half_past(2), half_past(2)]);
+    }
+
+    #[test]
+    fn ambiguous_fall_back_time_picks_earlier_instant() {
+        let rule = Recurrence::parse("FREQ=DAILY").unwrap();
+        let prev = ny(2026, 10, 31, 1, 30);
+        let next = rule.next_after(prev.naive_local(), &prev, &prev).unwrap();
+        assert_eq!(local_time(&next), NaiveTime::from_hms_opt(1, 30, 0).unwrap());
+        // 01:30 EDT, i.e. before the clocks go back.
+        assert_eq!(next.with_timezone(&Utc).naive_utc(), NaiveDate::from_ymd_opt(2026, 11, 1).unwrap().and_hms_opt(5, 30, 0).unwrap());
+
+        let after = rule.next_after(next.naive_local(), &next, &next).unwrap();
+        assert_eq!(after.naive_local(), NaiveDate::from_ymd_opt(2026, 11, 2).unwrap().and_hms_opt(1, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn weekly_upcoming_spans_dst_change() {
+        let rule = Recurrence::parse("FREQ=WEEKLY").unwrap();
+        let start = ny(2026, 3, 2, 8, 0); // Monday, EST
+        let dates: Vec<_> = rule.upcoming(&start, 3).iter().map(|d| d.naive_local()).collect();
+        let expected: Vec<_> = [9, 16, 23]
+            .iter()
+            .map(|&d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap().and_hms_opt(8, 0, 0).unwrap())
+            .collect();
+        assert_eq!(dates, expected);
+    }
+
+    #[test]
+    fn weekdays_skip_the_weekend() {
+        let rule = Recurrence::parse("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR").unwrap();
+        let friday = ny(2026, 6, 5, 9, 0);
+        let next = rule.next_after(friday.naive_local(), &friday, &friday).unwrap();
+        assert_eq!(next.weekday(), Weekday::Mon);
+        assert_eq!(next.date_naive(), NaiveDate::from_ymd_opt(2026, 6, 8).unwrap());
+    }
+
+    #[test]
+    fn biweekly_byday_counts_weeks_from_anchor_week() {
+        let rule = Recurrence::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR").unwrap();
+        let wednesday = ny(2026, 6, 3, 9, 0);
+        let days: Vec<_> = rule.upcoming(&wednesday, 3).iter().map(|d| d.date_naive().day()).collect();
+        // Friday of the anchor week, then Monday and Friday two weeks on.
+        assert_eq!(days, vec![5, 15, 19]);
+    }
+
+    #[test]
+    fn overdue_task_skips_missed_occurrences() {
+        let rule = Recurrence::parse("FREQ=DAILY;INTERVAL=3").unwrap();
+        let prev = ny(2026, 1, 1, 9, 0);
+        let now = ny(2026, 1, 10, 12, 0);
+        let next = rule.next_after(prev.naive_local(), &prev, &now).unwrap();
+        assert_eq!(next.naive_local(), NaiveDate::from_ymd_opt(2026, 1, 13).unwrap().and_hms_opt(9, 0, 0).unwrap());
+    }
+
+    fn hm(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn daily_due_before_and_after_the_scheduled_time() {
+        let last = ny(2026, 6, 9, 9, 0);
+        assert!(!daily_due(&ny(2026, 6, 10, 5, 59), hm(6, 0), &last));
+        assert!(daily_due(&ny(2026, 6, 10, 6, 0), hm(6, 0), &last));
+        assert!(daily_due(&ny(2026, 6, 10, 23, 0), hm(6, 0), &last));
+        // Once it has run, not again until tomorrow
+        let ran = ny(2026, 6, 10, 6, 1);
+        assert!(!daily_due(&ny(2026, 6, 10, 23, 0), hm(6, 0), &ran));
+        assert!(daily_due(&ny(2026, 6, 11, 6, 0), hm(6, 0), &ran));
+    }
+
+    #[test]
+    fn daily_due_catches_up_on_a_missed_day() {
+        // Asleep through yesterday's 23:00 rollover; today's isn't due yet
+        let last = ny(2026, 6, 8, 23, 0);
+        assert!(daily_due(&ny(2026, 6, 10, 8, 0), hm(23, 0), &last));
+        // Several days away still counts once: after it runs, it's not due
+        let ran = ny(2026, 6, 10, 8, 0);
+        assert!(!daily_due(&ny(2026, 6, 10, 22, 59), hm(23, 0), &ran));
+    }
+
+    #[test]
+    fn daily_due_in_spring_forward_gap_fires_after_the_gap() {
+        // 02:30 doesn't exist on 2026-03-08; it fires at 03:00 EDT instead
+        let last = ny(2026, 3, 7, 2, 30);
+        assert!(!daily_due(&ny(2026, 3, 8, 1, 59), hm(2, 30), &last));
+        assert!(daily_due(&ny(2026, 3, 8, 3, 0), hm(2, 30), &last));
+        assert!(!daily_due(&ny(2026, 3, 8, 12, 0), hm(2, 30), &ny(2026, 3, 8, 3, 0)));
+        assert!(daily_due(&ny(2026, 3, 9, 2, 30), hm(2, 30), &ny(2026, 3, 8, 3, 0)));
+    }
+
+    #[test]
+    fn parse_accepts_prefix_case_and_duplicate_days() {
+        let rule = Recurrence::parse(" RRULE:freq=weekly;byday=fr,mo,FR;interval=2 ").unwrap();
+        assert_eq!(rule.freq, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.by_day, vec![Weekday::Mon, Weekday::Fri]);
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR");
+        assert_eq!(Recurrence::parse(&rule.to_string()).unwrap(), rule);
+    }
+
+    #[test]
+    fn parse_rejects_unsupported_rules() {
+        for rule in [
+            "",
+            "INTERVAL=2",
+            "FREQ=MONTHLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;INTERVAL=x",
+            "FREQ=DAILY;BYDAY=MO",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=WEEKLY;COUNT=3",
+            "FREQ",
+        ] {
+            assert!(Recurrence::parse(rule).is_err(), "{:?} should be rejected", rule);
+        }
+    }
+
+    #[test]
+    fn serde_round_trips_through_rule_string() {
+        let rule = Recurrence::parse("FREQ=WEEKLY;BYDAY=TU").unwrap();
+        let json = serde_json::to_string(&rule).unwrap();
+        assert_eq!(json, "\"FREQ=WEEKLY;BYDAY=TU\"");
+        assert_eq!(serde_json::from_str::<Recurrence>(&json).unwrap(), rule);
+        assert!(serde_json::from_str::<Recurrence>("\"FREQ=YEARLY\"").is_err());
+    }
+}
@@ -0,0 +1,543 @@
+//! Pure space-topology logic, kept free of CoreGraphics so it can be driven
+//! by recorded `CGSCopyManagedDisplaySpaces` snapshots.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// One space as reported by `CGSCopyManagedDisplaySpaces`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManagedSpace {
+    /// CGS `id64`. Not stable: it can change after a reboot or a Mission
+    /// Control rearrangement.
+    pub space_id: i64,
+    /// CGS `uuid`, when the space has one (the first desktop often doesn't).
+    pub uuid: Option<String>,
+    pub display: usize,
+    /// 1-based index within its display (matches Ctrl+N shortcuts).
+    pub local: u32,
+    pub is_fullscreen: bool,
+    /// Process owning a full-screen space.
+    pub owner_pid: Option<i32>,
+}
+
+/// Extract every space from a `CGSCopyManagedDisplaySpaces` result: an
+/// array with one dictionary per display, each listing its `Spaces` in
+/// Mission Control order. `type` is 0 for a desktop and non-zero (usually 4)
+/// for a full-screen space, whose owner pid is either on the space itself or
+/// on the first tile of its `TileLayoutManager`.
+pub fn managed_spaces(displays: &plist::Value) -> Vec<ManagedSpace> {
+    let int = |dict: &plist::Dictionary, key: &str| dict.get(key).and_then(plist::Value::as_signed_integer);
+    let mut result = Vec::new();
+    for (display, dict) in displays.as_array().into_iter().flatten().enumerate() {
+        let Some(spaces) = dict.as_dictionary().and_then(|d| d.get("Spaces")).and_then(plist::Value::as_array) else {
+            continue;
+        };
+        // 1-based for Ctrl+N shortcuts
+        for (local, space) in (1..).zip(spaces.iter().filter_map(plist::Value::as_dictionary)) {
+            let is_fullscreen = int(space, "type") != Some(0);
+            let owner_pid = if is_fullscreen {
+                int(space, "pid")
+                    .or_else(|| {
+                        let manager = space.get("TileLayoutManager")?.as_dictionary()?;
+                        let tile = manager.get("TileSpaces")?.as_array()?.first()?.as_dictionary()?;
+                        int(tile, "pid")
+                    })
+                    .and_then(|pid| i32::try_from(pid).ok())
+                    .filter(|&pid| pid > 0)
+            } else {
+                None
+            };
+            result.push(ManagedSpace {
+                space_id: int(space, "id64").unwrap_or(0),
+                uuid: space.get("uuid").and_then(plist::Value::as_string).filter(|u| !u.is_empty()).map(str::to_string),
+                display,
+                local,
+                is_fullscreen,
+                owner_pid,
+            });
+        }
+    }
+    result
+}
+
+/// Numbering for one space after applying the full-screen settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpaceSlot {
+    /// Global 0-based index, used for default colors.
+    pub position: u32,
+    /// 1-based number within its display.
+    pub local: u32,
+    /// For full-screen spaces, the index of the desktop they belong to: the
+    /// closest regular desktop before them on the same display.
+    pub parent: Option<usize>,
+}
+
+/// Number `spaces` globally and per display. With `exclude_fullscreen`,
+/// full-screen spaces don't consume a number or color slot and share their
+/// parent desktop's instead. One with no desktop before it on its display
+/// has nothing to share, so it's numbered like a desktop.
+pub fn layout_spaces(spaces: &[ManagedSpace], exclude_fullscreen: bool) -> Vec<SpaceSlot> {
+    let mut slots: Vec<SpaceSlot> = Vec::with_capacity(spaces.len());
+    let mut next_position = 0;
+    let mut next_local: std::collections::HashMap<usize, u32> = std::collections::HashMap::new();
+    let mut last_desktop: std::collections::HashMap<usize, usize> = std::collections::HashMap::new();
+
+    for (i, space) in spaces.iter().enumerate() {
+        let parent = if space.is_fullscreen { last_desktop.get(&space.display).copied() } else { None };
+        let slot = match parent {
+            Some(p) if exclude_fullscreen => SpaceSlot { parent, ..slots[p] },
+            _ => {
+                let local = next_local.entry(space.display).or_insert(1);
+                let slot = SpaceSlot { position: next_position, local: *local, parent };
+                *local += 1;
+                next_position += 1;
+                slot
+            }
+        };
+        if !space.is_fullscreen {
+            last_desktop.insert(space.display, i);
+        }
+        slots.push(slot);
+    }
+    slots
+}
+
+/// A desktop the store knows about. `id` is the stable key for all
+/// per-desktop data; the other fields describe where it was last seen.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DesktopRecord {
+    pub id: i64,
+    #[serde(default)]
+    pub uuid: Option<String>,
+    pub space_id: i64,
+    pub display: usize,
+    pub local: u32,
+}
+
+type MatchPass = fn(&DesktopRecord, &ManagedSpace) -> bool;
+
+/// Two uuids contradict only when both are known and differ.
+fn uuids_conflict(a: &Option<String>, b: &Option<String>) -> bool {
+    matches!((a, b), (Some(a), Some(b)) if a != b)
+}
+
+/// Match the current topology against known desktops and return the stable
+/// desktop id for each space in `spaces`, in order.
+///
+/// Spaces are matched by uuid first, then by unchanged id64 when uuids can't
+/// be compared, then by display and position. Matched records are updated to
+/// the current id64/position; unmatched spaces get new records. Records with
+/// no current space are left in place so their data can be recovered.
+///
+/// New ids reuse the space's id64 only while `records` is empty (the first
+/// run after upgrading from id64-keyed data); afterwards they are allocated
+/// above every id in `records` and `reserved`. Returns whether `records`
+/// changed.
+pub fn reconcile(records: &mut Vec<DesktopRecord>, spaces: &[ManagedSpace], reserved: &HashSet<i64>) -> (Vec<i64>, bool) {
+    let initial = records.is_empty();
+    let mut taken = vec![false; records.len()];
+    let mut matched: Vec<Option<usize>> = vec![None; spaces.len()];
+
+    let passes: [MatchPass; 3] = [
+        |r, s| s.uuid.is_some() && r.uuid == s.uuid,
+        |r, s| r.space_id == s.space_id && (r.uuid.is_none() || s.uuid.is_none()),
+        |r, s| r.display == s.display && r.local == s.local && !uuids_conflict(&r.uuid, &s.uuid),
+    ];
+    for pass in passes {
+        for (i, space) in spaces.iter().enumerate() {
+            if matched[i].is_some() {
+                continue;
+            }
+            if let Some(r) = (0..records.len()).find(|&r| !taken[r] && pass(&records[r], space)) {
+                taken[r] = true;
+                matched[i] = Some(r);
+            }
+        }
+    }
+
+    let mut changed = false;
+    let mut next_id = records
+        .iter()
+        .map(|r| r.id)
+        .chain(reserved.iter().copied())
+        .max()
+        .map_or(1, |m| m + 1);
+    let mut ids = Vec::with_capacity(spaces.len());
+
+    for (space, slot) in spaces.iter().zip(matched) {
+        let record = DesktopRecord {
+            id: 0,
+            uuid: space.uuid.clone(),
+            space_id: space.space_id,
+            display: space.display,
+            local: space.local,
+        };
+        match slot {
+            Some(r) => {
+                let updated = DesktopRecord { id: records[r].id, ..record };
+                if records[r] != updated {
+                    records[r] = updated;
+                    changed = true;
+                }
+                ids.push(records[r].id);
+            }
+            None => {
+                let id = if initial && !records.iter().any(|r| r.id == space.space_id) {
+                    space.space_id
+                } else {
+                    while records.iter().any(|r| r.id == next_id) || reserved.contains(&next_id) {
+                        next_id += 1;
+                    }
+                    next_id
+                };
+                records.push(DesktopRecord { id, ..record });
+                changed = true;
+                ids.push(id);
+            }
+        }
+    }
+
+    (ids, changed)
+}
+
+/// Where a desktop sits in one topology snapshot.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SpacePosition {
+    pub desktop_id: i64,
+    pub display: usize,
+    /// Global 0-based index across all displays.
+    pub position: u32,
+    /// 1-based index within its display.
+    pub local: u32,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SpaceMove {
+    pub desktop_id: i64,
+    pub old: SpacePosition,
+    pub new: SpacePosition,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TopologyDiff {
+    pub added: Vec<SpacePosition>,
+    pub removed: Vec<SpacePosition>,
+    pub reordered: Vec<SpaceMove>,
+}
+
+impl TopologyDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.reordered.is_empty()
+    }
+}
+
+/// Compare two snapshots by desktop id. A desktop counts as reordered when
+/// it changed display or its order relative to the other surviving desktops
+/// on its display changed, so neighbours shifting because of an add or
+/// remove aren't reported as moves.
+pub fn diff_topology(old: &[SpacePosition], new: &[SpacePosition]) -> TopologyDiff {
+    let old_ids: HashSet<i64> = old.iter().map(|s| s.desktop_id).collect();
+    let new_ids: HashSet<i64> = new.iter().map(|s| s.desktop_id).collect();
+
+    let added = new.iter().filter(|s| !old_ids.contains(&s.desktop_id)).cloned().collect();
+    let removed = old.iter().filter(|s| !new_ids.contains(&s.desktop_id)).cloned().collect();
+
+    // (display, rank among survivors on that display) for each surviving desktop
+    let ranks = |snapshot: &[SpacePosition], keep: &HashSet<i64>| -> Vec<(i64, usize, usize)> {
+        let mut per_display: std::collections::HashMap<usize, usize> = std::collections::HashMap::new();
+        snapshot
+            .iter()
+            .filter(|s| keep.contains(&s.desktop_id))
+            .map(|s| {
+                let rank = per_display.entry(s.display).or_insert(0);
+                *rank += 1;
+                (s.desktop_id, s.display, *rank)
+            })
+            .collect()
+    };
+    let survivors: HashSet<i64> = old_ids.intersection(&new_ids).copied().collect();
+    let old_ranks = ranks(old, &survivors);
+    let new_ranks = ranks(new, &survivors);
+
+    let reordered = new_ranks
+        .iter()
+        .filter(|n| !old_ranks.contains(n))
+        .filter_map(|&(id, _, _)| {
+            let old = old.iter().find(|s| s.desktop_id == id)?;
+            let new = new.iter().find(|s| s.desktop_id == id)?;
+            Some(SpaceMove { desktop_id: id, old: old.clone(), new: new.clone() })
+        })
+        .collect();
+
+    TopologyDiff { added, removed, reordered }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// A recorded `CGSCopyManagedDisplaySpaces` result.
+    fn fixture(name: &str) -> Vec<ManagedSpace> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/spaces").join(name);
+        managed_spaces(&plist::Value::from_file(path).unwrap())
+    }
+
+    fn space(space_id: i64, uuid: Option<&str>, display: usize, local: u32) -> ManagedSpace {
+        ManagedSpace { space_id, uuid: uuid.map(str::to_string), display, local, is_fullscreen: false, owner_pid: None }
+    }
+
+    /// Two displays: three desktops on the main one, two on the external.
+    fn two_displays() -> Vec<ManagedSpace> {
+        vec![
+            space(1, None, 0, 1),
+            space(5, Some("A"), 0, 2),
+            space(6, Some("B"), 0, 3),
+            space(9, Some("X"), 1, 1),
+            space(10, Some("Y"), 1, 2),
+        ]
+    }
+
+    fn fullscreen(space_id: i64, display: usize, local: u32) -> ManagedSpace {
+        ManagedSpace { is_fullscreen: true, owner_pid: Some(100), ..space(space_id, None, display, local) }
+    }
+
+    #[test]
+    fn layout_excluded_fullscreen_shares_parent_slot() {
+        let spaces = vec![space(1, None, 0, 1), fullscreen(2, 0, 2), space(3, Some("A"), 0, 3)];
+        let slots = layout_spaces(&spaces, true);
+        assert_eq!(slots[1], SpaceSlot { position: 0, local: 1, parent: Some(0) });
+        assert_eq!(slots[2], SpaceSlot { position: 1, local: 2, parent: None });
+
+        let slots = layout_spaces(&spaces, false);
+        assert_eq!(slots[1], SpaceSlot { position: 1, local: 2, parent: Some(0) });
+        assert_eq!(slots[2], SpaceSlot { position: 2, local: 3, parent: None });
+    }
+
+    #[test]
+    fn layout_leading_fullscreen_gets_its_own_slot() {
+        // The external display starts with a full-screen space
+        let spaces = vec![space(1, None, 0, 1), fullscreen(2, 1, 1), space(3, Some("A"), 1, 2)];
+        let slots = layout_spaces(&spaces, true);
+        assert_eq!(slots[1], SpaceSlot { position: 1, local: 1, parent: None });
+        assert_eq!(slots[2], SpaceSlot { position: 2, local: 2, parent: None });
+    }
+
+    fn seeded(spaces: &[ManagedSpace]) -> Vec<DesktopRecord> {
+        let mut records = Vec::new();
+        reconcile(&mut records, spaces, &HashSet::new());
+        records
+    }
+
+    #[test]
+    fn first_run_reuses_id64() {
+        let mut records = Vec::new();
+        let (ids, changed) = reconcile(&mut records, &two_displays(), &HashSet::new());
+        assert_eq!(ids, vec![1, 5, 6, 9, 10]);
+        assert!(changed);
+        assert_eq!(records.len(), 5);
+    }
+
+    #[test]
+    fn unchanged_topology_is_not_a_change() {
+        let mut records = seeded(&two_displays());
+        let before = records.clone();
+        let (ids, changed) = reconcile(&mut records, &two_displays(), &HashSet::new());
+        assert_eq!(ids, vec![1, 5, 6, 9, 10]);
+        assert!(!changed);
+        assert_eq!(records, before);
+    }
+
+    #[test]
+    fn reorder_follows_uuid_and_id64() {
+        let mut records = seeded(&two_displays());
+        // B dragged to the front of the main display
+        let reordered = vec![
+            space(6, Some("B"), 0, 1),
+            space(1, None, 0, 2),
+            space(5, Some("A"), 0, 3),
+            space(9, Some("X"), 1, 1),
+            space(10, Some("Y"), 1, 2),
+        ];
+        let (ids, changed) = reconcile(&mut records, &reordered, &HashSet::new());
+        assert_eq!(ids, vec![6, 1, 5, 9, 10]);
+        assert!(changed);
+        let b = records.iter().find(|r| r.id == 6).unwrap();
+        assert_eq!(b.local, 1);
+    }
+
+    #[test]
+    fn add_and_remove_keep_old_records() {
+        let mut records = seeded(&two_displays());
+        // A closed, a new desktop opened at the end
+        let spaces = vec![
+            space(1, None, 0, 1),
+            space(6, Some("B"), 0, 2),
+            space(12, Some("C"), 0, 3),
+            space(9, Some("X"), 1, 1),
+            space(10, Some("Y"), 1, 2),
+        ];
+        let (ids, changed) = reconcile(&mut records, &spaces, &HashSet::new());
+        assert!(changed);
+        // New ids are allocated above every known id, not taken from id64
+        assert_eq!(ids, vec![1, 6, 11, 9, 10]);
+        // A's record stays so its data can be recovered
+        assert!(records.iter().any(|r| r.id == 5 && r.uuid.as_deref() == Some("A")));
+        assert_eq!(records.len(), 6);
+    }
+
+    #[test]
+    fn new_ids_skip_reserved() {
+        let mut records = seeded(&two_displays());
+        let mut spaces = two_displays();
+        spaces.push(space(3, Some("D"), 0, 4));
+        let reserved: HashSet<i64> = [11, 12].into_iter().collect();
+        let (ids, _) = reconcile(&mut records, &spaces, &reserved);
+        assert_eq!(ids.last(), Some(&13));
+    }
+
+    #[test]
+    fn display_reconnect_after_reboot() {
+        let mut records = seeded(&two_displays());
+        // External display unplugged
+        let (ids, _) = reconcile(&mut records, &two_displays()[..3], &HashSet::new());
+        assert_eq!(ids, vec![1, 5, 6]);
+        // Plugged back in after a reboot: every id64 changed, uuids didn't,
+        // and the external display's desktops came back in a different order.
+        let spaces = vec![
+            space(40, None, 0, 1),
+            space(41, Some("A"), 0, 2),
+            space(42, Some("B"), 0, 3),
+            space(43, Some("Y"), 1, 1),
+            space(44, Some("X"), 1, 2),
+        ];
+        let (ids, changed) = reconcile(&mut records, &spaces, &HashSet::new());
+        // The uuid-less main desktop is matched by position
+        assert_eq!(ids, vec![1, 5, 6, 10, 9]);
+        assert!(changed);
+        assert_eq!(records.len(), 5);
+        let x = records.iter().find(|r| r.id == 9).unwrap();
+        assert_eq!((x.space_id, x.display, x.local), (44, 1, 2));
+    }
+
+    #[test]
+    fn uuid_appearing_keeps_the_desktop() {
+        let mut records = seeded(&two_displays());
+        let mut spaces = two_displays();
+        spaces[0].uuid = Some("MAIN".to_string());
+        let (ids, changed) = reconcile(&mut records, &spaces, &HashSet::new());
+        assert_eq!(ids[0], 1);
+        assert!(changed);
+        assert_eq!(records[0].uuid.as_deref(), Some("MAIN"));
+    }
+
+    #[test]
+    fn uuid_change_at_same_position_is_a_new_desktop() {
+        let mut records = seeded(&two_displays());
+        // A was closed and a new desktop took its place and id64
+        let mut spaces = two_displays();
+        spaces[1].uuid = Some("A2".to_string());
+        let (ids, changed) = reconcile(&mut records, &spaces, &HashSet::new());
+        assert_eq!(ids, vec![1, 11, 6, 9, 10]);
+        assert!(changed);
+        assert!(records.iter().any(|r| r.id == 5 && r.uuid.as_deref() == Some("A")));
+    }
+
+    fn pos(desktop_id: i64, display: usize, position: u32, local: u32) -> SpacePosition {
+        SpacePosition { desktop_id, display, position, local }
+    }
+
+    /// Desktops 1–3 on the main display, 4–5 on the external one.
+    fn snapshot() -> Vec<SpacePosition> {
+        vec![pos(1, 0, 0, 1), pos(2, 0, 1, 2), pos(3, 0, 2, 3), pos(4, 1, 3, 1), pos(5, 1, 4, 2)]
+    }
+
+    #[test]
+    fn diff_of_identical_snapshots_is_empty() {
+        assert!(diff_topology(&snapshot(), &snapshot()).is_empty());
+    }
+
+    #[test]
+    fn diff_reports_added_and_removed() {
+        let new = vec![pos(1, 0, 0, 1), pos(3, 0, 1, 2), pos(6, 0, 2, 3), pos(4, 1, 3, 1), pos(5, 1, 4, 2)];
+        let diff = diff_topology(&snapshot(), &new);
+        assert_eq!(diff.added, vec![pos(6, 0, 2, 3)]);
+        assert_eq!(diff.removed, vec![pos(2, 0, 1, 2)]);
+        // 3 shifted left because 2 closed, but its order among survivors didn't change
+        assert!(diff.reordered.is_empty());
+    }
+
+    #[test]
+    fn diff_reports_swapped_desktops() {
+        let new = vec![pos(2, 0, 0, 1), pos(1, 0, 1, 2), pos(3, 0, 2, 3), pos(4, 1, 3, 1), pos(5, 1, 4, 2)];
+        let diff = diff_topology(&snapshot(), &new);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        let moved: Vec<i64> = diff.reordered.iter().map(|m| m.desktop_id).collect();
+        assert_eq!(moved, vec![2, 1]);
+        assert_eq!(diff.reordered[0].old, pos(2, 0, 1, 2));
+        assert_eq!(diff.reordered[0].new, pos(2, 0, 0, 1));
+    }
+
+    #[test]
+    fn diff_reports_display_moves() {
+        let new = vec![pos(1, 0, 0, 1), pos(2, 0, 1, 2), pos(4, 1, 2, 1), pos(3, 1, 3, 2), pos(5, 1, 4, 3)];
+        let diff = diff_topology(&snapshot(), &new);
+        let moved: Vec<i64> = diff.reordered.iter().map(|m| m.desktop_id).collect();
+        // 3 changed display; 5 changed rank on the external display
+        assert_eq!(moved, vec![3, 5]);
+    }
+
+    #[test]
+    fn diff_of_display_removal() {
+        let diff = diff_topology(&snapshot(), &snapshot()[..3]);
+        assert_eq!(diff.removed.iter().map(|s| s.desktop_id).collect::<Vec<_>>(), vec![4, 5]);
+        assert!(diff.added.is_empty() && diff.reordered.is_empty());
+    }
+
+    #[test]
+    fn extracts_spaces_on_multiple_displays() {
+        let spaces = fixture("multiple_displays.plist");
+        let shape = |spaces: &[ManagedSpace]| -> Vec<(i64, usize, u32, bool, bool)> {
+            spaces.iter().map(|s| (s.space_id, s.display, s.local, s.uuid.is_some(), s.is_fullscreen)).collect()
+        };
+        assert_eq!(shape(&spaces), shape(&two_displays()));
+        // The main desktop's empty uuid is treated as missing
+        assert_eq!(spaces[0].uuid, None);
+        assert_eq!(spaces[1].uuid.as_deref(), Some("4B1C2D8E-6F0A-4E3B-9C51-2A7D8E9F0B13"));
+    }
+
+    #[test]
+    fn extracts_fullscreen_spaces_and_owners() {
+        let spaces = fixture("fullscreen.plist");
+        let summary: Vec<(i64, u32, bool, Option<i32>)> =
+            spaces.iter().map(|s| (s.space_id, s.local, s.is_fullscreen, s.owner_pid)).collect();
+        // Safari's pid is on its first tile; Mail's is on the space itself
+        assert_eq!(summary, vec![(1, 1, false, None), (7, 2, true, Some(812)), (5, 3, false, None), (8, 4, true, Some(933))]);
+        assert_eq!(spaces[0].uuid, None);
+        assert_eq!(spaces[3].uuid, None);
+
+        let slots = layout_spaces(&spaces, true);
+        assert_eq!(slots[1].parent, Some(0));
+        assert_eq!(slots[3], SpaceSlot { parent: Some(2), ..slots[2] });
+    }
+
+    #[test]
+    fn reordered_fixture_keeps_desktop_ids() {
+        let mut records = seeded(&fixture("multiple_displays.plist"));
+        let spaces = fixture("reordered.plist");
+        assert_eq!(spaces.iter().map(|s| s.local).collect::<Vec<_>>(), vec![1, 2, 3, 1, 2]);
+        let (ids, changed) = reconcile(&mut records, &spaces, &HashSet::new());
+        assert_eq!(ids, vec![6, 1, 5, 9, 10]);
+        assert!(changed);
+    }
+
+    #[test]
+    fn extraction_skips_malformed_entries() {
+        let mut display = plist::Dictionary::new();
+        display.insert("Spaces".into(), plist::Value::Array(vec![plist::Value::Integer(3.into())]));
+        let displays = plist::Value::Array(vec![plist::Value::Dictionary(plist::Dictionary::new()), display.into()]);
+        assert!(managed_spaces(&displays).is_empty());
+        assert!(managed_spaces(&plist::Value::Boolean(true)).is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+//! Desktop switching strategy, independent of how key events are posted.
+//!
+//! Everything that touches the system goes through [`SpaceSource`], so the
+//! switch-and-confirm flow can be driven by a fake that ignores or delays
+//! switches.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::spaces::ManagedSpace;
+
+/// macOS only ships "Switch to Desktop N" shortcuts for desktops 1–9.
+pub const MAX_DIRECT_DESKTOP: u32 = 9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwitchPlan {
+    /// Press Ctrl+N for desktop number N on the display.
+    Direct(u32),
+    /// Press Ctrl+Arrow this many times (negative = left).
+    Step(i32),
+}
+
+/// The Mission Control desktop number of `display_spaces[index]`. Full-screen
+/// spaces aren't numbered, so they can't be reached with Ctrl+N.
+pub fn desktop_number(display_spaces: &[ManagedSpace], index: usize) -> Option<u32> {
+    let target = display_spaces.get(index)?;
+    if target.is_fullscreen {
+        return None;
+    }
+    let number = display_spaces[..=index].iter().filter(|s| !s.is_fullscreen).count() as u32;
+    Some(number)
+}
+
+/// Choose how to get from `current` to `target` (indices into the spaces of
+/// one display). Prefers a direct Ctrl+N jump when the target has a desktop
+/// number and `direct_enabled` reports that shortcut as turned on; otherwise
+/// steps with Ctrl+Arrow. Returns `None` when already there.
+pub fn plan_switch(
+    display_spaces: &[ManagedSpace],
+    current: usize,
+    target: usize,
+    direct_enabled: impl Fn(u32) -> bool,
+) -> Option<SwitchPlan> {
+    if current == target {
+        return None;
+    }
+    match desktop_number(display_spaces, target) {
+        Some(n) if n <= MAX_DIRECT_DESKTOP && direct_enabled(n) => Some(SwitchPlan::Direct(n)),
+        _ => Some(SwitchPlan::Step(target as i32 - current as i32)),
+    }
+}
+
+/// Outcome of a switch request, as reported to the UI.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchResult {
+    /// The target became the active space.
+    Switched,
+    /// The target was already active; no keys were sent.
+    AlreadyThere,
+    /// The target desktop isn't on the requested display (or no longer exists).
+    TargetNotOnDisplay,
+    /// Accessibility permission is missing, so posted keys would be dropped.
+    PermissionDenied,
+    /// The active space isn't on the display and the target has no enabled
+    /// Ctrl+N shortcut, so no keys can get there.
+    NoPlan,
+    /// Keys were sent but the active space didn't become the target in time.
+    TimedOut,
+}
+
+/// The system side of a switch: reading the active space, posting keys and
+/// observing space changes.
+pub trait SpaceSource {
+    /// CGS id64 of the active space on `display`.
+    fn active_space(&self, display: usize) -> i64;
+    /// Whether synthetic key events will be delivered.
+    fn can_post_keys(&self) -> bool;
+    /// Whether the "Switch to Desktop `number`" shortcut is enabled.
+    fn has_direct_shortcut(&self, number: u32) -> bool;
+    fn post(&self, plan: SwitchPlan);
+    /// Counter bumped on every observed active-space change.
+    fn change_count(&self) -> u64;
+    /// Block until `change_count()` differs from `seen` or `timeout` passes.
+    fn wait_for_change(&self, seen: u64, timeout: Duration);
+}
+
+/// Switch `display` to the space with id64 `target` and confirm it became
+/// active within `timeout` of the keys being posted.
+pub fn switch_to<S: SpaceSource>(
+    source: &S,
+    display: usize,
+    display_spaces: &[ManagedSpace],
+    target: Option<i64>,
+    timeout: Duration,
+) -> SwitchResult {
+    let Some((target_pos, target_sid)) = target
+        .and_then(|sid| display_spaces.iter().position(|s| s.space_id == sid).map(|pos| (pos, sid)))
+    else {
+        return SwitchResult::TargetNotOnDisplay;
+    };
+
+    let current_sid = source.active_space(display);
+    if current_sid == target_sid {
+        return SwitchResult::AlreadyThere;
+    }
+    if !source.can_post_keys() {
+        return SwitchResult::PermissionDenied;
+    }
+
+    let plan = match display_spaces.iter().position(|s| s.space_id == current_sid) {
+        Some(current_pos) => plan_switch(display_spaces, current_pos, target_pos, |n| source.has_direct_shortcut(n)),
+        // Without a known starting point only a direct jump can work.
+        None => desktop_number(display_spaces, target_pos)
+            .filter(|&n| n <= MAX_DIRECT_DESKTOP && source.has_direct_shortcut(n))
+            .map(SwitchPlan::Direct),
+    };
+    let Some(plan) = plan else {
+        return SwitchResult::NoPlan;
+    };
+
+    let mut seen = source.change_count();
+    source.post(plan);
+    let deadline = Instant::now() + timeout;
+    loop {
+        if source.active_space(display) == target_sid {
+            return SwitchResult::Switched;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return SwitchResult::TimedOut;
+        }
+        source.wait_for_change(seen, deadline - now);
+        seen = source.change_count();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    fn space(space_id: i64, is_fullscreen: bool) -> ManagedSpace {
+        ManagedSpace { space_id, uuid: None, display: 0, local: 0, is_fullscreen, owner_pid: None }
+    }
+
+    /// How the fake reacts to posted keys.
+    #[derive(Clone, Copy)]
+    enum Response {
+        Immediate,
+        Ignore,
+        After(Duration),
+    }
+
+    /// One display whose active space follows posted plans.
+    struct FakeSpaces {
+        spaces: Vec<ManagedSpace>,
+        active: Cell<i64>,
+        can_post: bool,
+        shortcuts: Vec<u32>,
+        response: Response,
+        /// Space and time a delayed switch lands.
+        pending: Cell<Option<(i64, Instant)>>,
+        changes: Cell<u64>,
+        posted: RefCell<Vec<SwitchPlan>>,
+    }
+
+    impl FakeSpaces {
+        fn new(spaces: Vec<ManagedSpace>, active: i64) -> Self {
+            FakeSpaces {
+                spaces,
+                active: Cell::new(active),
+                can_post: true,
+                shortcuts: (1..=MAX_DIRECT_DESKTOP).collect(),
+                response: Response::Immediate,
+                pending: Cell::new(None),
+                changes: Cell::new(0),
+                posted: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn target_of(&self, plan: SwitchPlan) -> Option<i64> {
+            match plan {
+                SwitchPlan::Direct(n) => self.spaces.iter().filter(|s| !s.is_fullscreen).nth(n as usize - 1),
+                SwitchPlan::Step(steps) => {
+                    let current = self.spaces.iter().position(|s| s.space_id == self.active.get())?;
+                    self.spaces.get(current.checked_add_signed(steps as isize)?)
+                }
+            }
+            .map(|s| s.space_id)
+        }
+
+        fn land(&self, sid: i64) {
+            self.active.set(sid);
+            self.changes.set(self.changes.get() + 1);
+        }
+
+        fn land_if_due(&self) {
+            if let Some((sid, at)) = self.pending.get() {
+                if Instant::now() >= at {
+                    self.pending.set(None);
+                    self.land(sid);
+                }
+            }
+        }
+    }
+
+    impl SpaceSource for FakeSpaces {
+        fn active_space(&self, _display: usize) -> i64 {
+            self.land_if_due();
+            self.active.get()
+        }
+
+        fn can_post_keys(&self) -> bool {
+            self.can_post
+        }
+
+        fn has_direct_shortcut(&self, number: u32) -> bool {
+            self.shortcuts.contains(&number)
+        }
+
+        fn post(&self, plan: SwitchPlan) {
+            self.posted.borrow_mut().push(plan);
+            let Some(sid) = self.target_of(plan) else { return };
+            match self.response {
+                Response::Immediate => self.land(sid),
+                Response::Ignore => {}
+                Response::After(delay) => self.pending.set(Some((sid, Instant::now() + delay))),
+            }
+        }
+
+        fn change_count(&self) -> u64 {
+            self.changes.get()
+        }
+
+        fn wait_for_change(&self, seen: u64, timeout: Duration) {
+            let deadline = Instant::now() + timeout;
+            while self.changes.get() == seen && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(1));
+                self.land_if_due();
+            }
+        }
+    }
+
+    /// Desktop 1, a full-screen space, then desktops 2 and 3.
+    fn display() -> Vec<ManagedSpace> {
+        vec![space(10, false), space(11, true), space(12, false), space(13, false)]
+    }
+
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    #[test]
+    fn desktop_numbers_skip_fullscreen() {
+        let spaces = display();
+        assert_eq!(desktop_number(&spaces, 0), Some(1));
+        assert_eq!(desktop_number(&spaces, 1), None);
+        assert_eq!(desktop_number(&spaces, 3), Some(3));
+    }
+
+    #[test]
+    fn plan_prefers_enabled_direct_shortcut() {
+        let spaces = display();
+        assert_eq!(plan_switch(&spaces, 0, 3, |_| true), Some(SwitchPlan::Direct(3)));
+        assert_eq!(plan_switch(&spaces, 0, 3, |n| n != 3), Some(SwitchPlan::Step(3)));
+        assert_eq!(plan_switch(&spaces, 3, 1, |_| true), Some(SwitchPlan::Step(-2)));
+        assert_eq!(plan_switch(&spaces, 2, 2, |_| true), None);
+    }
+
+    #[test]
+    fn successful_switch() {
+        let fake = FakeSpaces::new(display(), 10);
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(13), TIMEOUT), SwitchResult::Switched);
+        assert_eq!(*fake.posted.borrow(), vec![SwitchPlan::Direct(3)]);
+        assert_eq!(fake.active.get(), 13);
+    }
+
+    #[test]
+    fn stepping_switch_without_shortcut() {
+        let mut fake = FakeSpaces::new(display(), 13);
+        fake.shortcuts.clear();
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(11), TIMEOUT), SwitchResult::Switched);
+        assert_eq!(*fake.posted.borrow(), vec![SwitchPlan::Step(-2)]);
+    }
+
+    #[test]
+    fn ignored_switch_times_out() {
+        let mut fake = FakeSpaces::new(display(), 10);
+        fake.response = Response::Ignore;
+        let started = Instant::now();
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(12), TIMEOUT), SwitchResult::TimedOut);
+        assert!(started.elapsed() >= TIMEOUT);
+        assert_eq!(fake.posted.borrow().len(), 1);
+    }
+
+    #[test]
+    fn delayed_switch_within_timeout() {
+        let mut fake = FakeSpaces::new(display(), 10);
+        fake.response = Response::After(Duration::from_millis(50));
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(12), TIMEOUT), SwitchResult::Switched);
+    }
+
+    #[test]
+    fn delayed_switch_past_timeout() {
+        let mut fake = FakeSpaces::new(display(), 10);
+        fake.response = Response::After(Duration::from_millis(500));
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(12), Duration::from_millis(50)), SwitchResult::TimedOut);
+    }
+
+    #[test]
+    fn no_keys_sent_when_not_needed_or_not_possible() {
+        let fake = FakeSpaces::new(display(), 12);
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(12), TIMEOUT), SwitchResult::AlreadyThere);
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(99), TIMEOUT), SwitchResult::TargetNotOnDisplay);
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, None, TIMEOUT), SwitchResult::TargetNotOnDisplay);
+
+        let mut fake = FakeSpaces::new(display(), 10);
+        fake.can_post = false;
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(12), TIMEOUT), SwitchResult::PermissionDenied);
+        assert!(fake.posted.borrow().is_empty());
+    }
+
+    #[test]
+    fn unknown_start_needs_a_direct_shortcut() {
+        // The active space is on another display
+        let fake = FakeSpaces::new(display(), 99);
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(12), TIMEOUT), SwitchResult::Switched);
+        assert_eq!(*fake.posted.borrow(), vec![SwitchPlan::Direct(2)]);
+
+        let fake = FakeSpaces::new(display(), 99);
+        assert_eq!(switch_to(&fake, 0, &fake.spaces, Some(11), TIMEOUT), SwitchResult::NoPlan);
+        assert!(fake.posted.borrow().is_empty());
+    }
+}
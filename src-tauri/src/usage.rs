@@ -0,0 +1,211 @@
+//! Per-desktop app usage: which apps are frontmost on which desktop, and for
+//! how long.
+//!
+//! The tracker turns a stream of "frontmost app / active desktop changed"
+//! events into finished segments; `record` folds segments into totals.
+//! The running segment is also cut at regular checkpoints, so long sessions
+//! are counted in full and time spent away from the keyboard isn't.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How often the running segment is checkpointed.
+pub const CHECKPOINT_SECS: u64 = 60;
+
+/// Checkpoints keep segments short, so a longer one means they stopped
+/// ticking (the Mac slept). Such segments are capped here.
+pub const MAX_SEGMENT_SECS: i64 = 2 * CHECKPOINT_SECS as i64;
+
+/// No input for this long counts as being away: the time after the last
+/// input isn't counted.
+pub const IDLE_SECS: i64 = 5 * 60;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AppUsage {
+    pub bundle_id: String,
+    pub name: String,
+    pub seconds: u64,
+    /// Times the app became frontmost on this desktop.
+    pub activations: u32,
+    /// RFC 3339.
+    pub last_used: String,
+}
+
+/// A stretch of time with one app frontmost on one desktop.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub desktop: i64,
+    pub bundle_id: String,
+    pub name: String,
+    pub started: DateTime<Utc>,
+    /// Continues a segment cut at a checkpoint rather than a new activation.
+    pub resumed: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    current: Option<Segment>,
+}
+
+impl UsageTracker {
+    pub const fn new() -> Self {
+        Self { current: None }
+    }
+
+    /// The frontmost app or the active desktop changed. `next` is the new
+    /// (desktop, bundle id, app name), or `None` when nothing should be
+    /// tracked. Returns the segment that just ended and its length in seconds.
+    pub fn transition(&mut self, next: Option<(i64, String, String)>, now: DateTime<Utc>) -> Option<(Segment, u64)> {
+        if let (Some(current), Some((desktop, bundle_id, _))) = (&self.current, &next) {
+            if current.desktop == *desktop && current.bundle_id == *bundle_id {
+                return None;
+            }
+        }
+        let finished = self.current.take().map(|segment| {
+            let secs = (now - segment.started).num_seconds().clamp(0, MAX_SEGMENT_SECS) as u64;
+            (segment, secs)
+        });
+        self.current = next.map(|(desktop, bundle_id, name)| Segment { desktop, bundle_id, name, started: now, resumed: false });
+        finished
+    }
+
+    /// Cut the running segment at `now` and carry on with the same app.
+    /// With no input for `idle_secs` ≥ `IDLE_SECS`, the finished segment ends
+    /// at the last input instead. Returns the finished segment and its length.
+    pub fn checkpoint(&mut self, now: DateTime<Utc>, idle_secs: i64) -> Option<(Segment, u64)> {
+        let current = self.current.as_mut()?;
+        let end = if idle_secs >= IDLE_SECS { now - Duration::seconds(idle_secs) } else { now };
+        let secs = (end - current.started).num_seconds().clamp(0, MAX_SEGMENT_SECS) as u64;
+        let finished = current.clone();
+        current.started = now;
+        current.resumed = true;
+        Some((finished, secs))
+    }
+}
+
+/// Add a finished segment to one desktop's totals. Returns whether they
+/// changed: an empty resumed segment (away the whole time) adds nothing.
+pub fn record(usage: &mut Vec<AppUsage>, segment: &Segment, secs: u64, now: DateTime<Utc>) -> bool {
+    if segment.resumed && secs == 0 {
+        return false;
+    }
+    let last_used = now.to_rfc3339();
+    let activations = u32::from(!segment.resumed);
+    match usage.iter_mut().find(|u| u.bundle_id == segment.bundle_id) {
+        Some(entry) => {
+            entry.name = segment.name.clone();
+            entry.seconds += secs;
+            entry.activations += activations;
+            entry.last_used = last_used;
+        }
+        None => usage.push(AppUsage {
+            bundle_id: segment.bundle_id.clone(),
+            name: segment.name.clone(),
+            seconds: secs,
+            activations,
+            last_used,
+        }),
+    }
+    true
+}
+
+/// Fold `from` into `into`, summing totals per app.
+pub fn merge(into: &mut Vec<AppUsage>, from: Vec<AppUsage>) {
+    for app in from {
+        match into.iter_mut().find(|u| u.bundle_id == app.bundle_id) {
+            Some(entry) => {
+                entry.seconds += app.seconds;
+                entry.activations += app.activations;
+                if app.last_used > entry.last_used {
+                    entry.last_used = app.last_used;
+                    entry.name = app.name;
+                }
+            }
+            None => into.push(app),
+        }
+    }
+}
+
+/// Most-used apps first: by time, then by activations.
+pub fn top_apps(usage: &[AppUsage], limit: usize) -> Vec<AppUsage> {
+    let mut apps = usage.to_vec();
+    apps.sort_by(|a, b| {
+        b.seconds
+            .cmp(&a.seconds)
+            .then(b.activations.cmp(&a.activations))
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    apps.truncate(limit);
+    apps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn safari(desktop: i64) -> Option<(i64, String, String)> {
+        Some((desktop, "com.apple.Safari".to_string(), "Safari".to_string()))
+    }
+
+    /// Feed the tracker's finished segments into one usage list.
+    fn run(steps: impl IntoIterator<Item = Option<(Segment, u64)>>, now: DateTime<Utc>) -> Vec<AppUsage> {
+        let mut usage = Vec::new();
+        for (segment, secs) in steps.into_iter().flatten() {
+            record(&mut usage, &segment, secs, now);
+        }
+        usage
+    }
+
+    #[test]
+    fn checkpoints_count_long_sessions_in_full() {
+        let mut tracker = UsageTracker::new();
+        let mut steps = vec![tracker.transition(safari(1), at(0))];
+        for minute in 1..=90 {
+            steps.push(tracker.checkpoint(at(minute * 60), 0));
+        }
+        steps.push(tracker.transition(None, at(90 * 60 + 30)));
+        let usage = run(steps, at(90 * 60 + 30));
+        assert_eq!(usage[0].seconds, 90 * 60 + 30);
+        assert_eq!(usage[0].activations, 1);
+    }
+
+    #[test]
+    fn idle_time_is_not_counted() {
+        let mut tracker = UsageTracker::new();
+        let steps = vec![
+            tracker.transition(safari(1), at(0)),
+            // Last input at 2 minutes, then away
+            tracker.checkpoint(at(60), 0),
+            tracker.checkpoint(at(120), 0),
+            tracker.checkpoint(at(420), 300),
+            tracker.checkpoint(at(480), 360),
+            // Back: input just now
+            tracker.checkpoint(at(540), 0),
+        ];
+        let usage = run(steps, at(540));
+        // 0–120 active, 420–480 away, 480–540 back
+        assert_eq!(usage[0].seconds, 120 + 60);
+        assert_eq!(usage[0].activations, 1);
+    }
+
+    #[test]
+    fn sleep_without_checkpoints_is_capped() {
+        let mut tracker = UsageTracker::new();
+        tracker.transition(safari(1), at(0));
+        let (_, secs) = tracker.transition(safari(2), at(8 * 3600)).unwrap();
+        assert_eq!(secs, MAX_SEGMENT_SECS as u64);
+    }
+
+    #[test]
+    fn same_app_and_desktop_is_not_a_transition() {
+        let mut tracker = UsageTracker::new();
+        tracker.transition(safari(1), at(0));
+        assert!(tracker.transition(safari(1), at(10)).is_none());
+        let (segment, secs) = tracker.transition(safari(2), at(20)).unwrap();
+        assert_eq!((segment.desktop, secs), (1, 20));
+    }
+}
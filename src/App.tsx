@@ -9,7 +9,7 @@ import { buildTheme } from "./theme";
 import { DEFAULT_BG, WINDOW_WIDTH, computeExpandedHeight } from "./constants";
 import { currentWindow, friendlyMonitorName, loadAnchor } from "./utils";
 import { changelog } from "./changelog";
-import type { DesktopInfo, DesktopSummary, Settings } from "./types";
+import type { DesktopInfo, DesktopSummary, HotkeyAction, Settings } from "./types";
 
 import LoadingView from "./components/LoadingView";
 import SetupView from "./components/SetupView";
@@ -108,7 +108,7 @@ function App() {
       if (action === "continue") {
         setView("todos");
       } else if (action === "new") {
-        useTodoStore.getState().clearAll();
+        useTodoStore.getState().clearSession();
         setView("todos");
       } else if (action === "history") {
         // The initiating window loads history data and navigates to history-picker.
@@ -120,6 +120,63 @@ function App() {
     return () => { unlisten.then((fn) => fn()); };
   }, [setView]);
 
+  // ── Reload todos after an automatic session rollover ──
+  useEffect(() => {
+    const unlisten = listen("session-rollover", async () => {
+      // Drop pending saves so the pre-rollover list isn't written back.
+      const todoState = useTodoStore.getState();
+      if (todoState.saveTimer) clearTimeout(todoState.saveTimer);
+      if (todoState.titleTimer) clearTimeout(todoState.titleTimer);
+      useTodoStore.setState({ saveTimer: null, titleTimer: null });
+      try {
+        const desktops = await invoke<DesktopSummary[]>("list_all_desktops");
+        await useTodoStore.getState().loadAll(desktops.map((d) => d.space_id));
+        useUIStore.getState().refreshDisplayGroups();
+        info("[rollover] todos reloaded");
+      } catch (err) {
+        error(`[rollover] failed to reload todos: ${err}`);
+      }
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
+  // ── Global hotkey actions ─────────────────────────────
+  // The backend shows the main window first; only it handles these.
+  useEffect(() => {
+    if (displayIndex !== 0) return;
+    const unlisten = listen<HotkeyAction>("hotkey-triggered", async (event) => {
+      const ui = useUIStore.getState();
+      if (ui.view === "loading" || ui.view === "setup" || ui.view === "session-chooser") return;
+      ui.setView("todos");
+      if (ui.collapsed) await ui.toggleMinimize();
+
+      switch (event.payload) {
+        case "quick_add_todo":
+          ui.focusTodoInput();
+          break;
+        case "start_timer": {
+          const { timerPresets, notifySystem } = useSettingsStore.getState();
+          const timers = useTimerStore.getState();
+          const timer = timers.getActiveTimer();
+          if (timer.running || timer.paused || timerPresets.length === 0) break;
+          timers.populateFromPreset(timerPresets[0]);
+          useTimerStore.getState().startTimer(notifySystem, false);
+          break;
+        }
+        case "open_switcher": {
+          const { hiddenPanels, setHiddenPanels } = useSettingsStore.getState();
+          if (hiddenPanels.includes("Desktops")) {
+            setHiddenPanels(hiddenPanels.filter((p) => p !== "Desktops"));
+          }
+          ui.focusSwitcher();
+          break;
+        }
+      }
+      info(`[hotkeys] handled ${event.payload}`);
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, [displayIndex]);
+
   // ── Fetch monitor info and refresh on monitor changes ──
   useEffect(() => {
     const refreshMonitorRef = () => {
@@ -233,7 +290,7 @@ function App() {
       });
 
     // Listen for space-change events from NSWorkspace observer
-    const unlisten = listen<DesktopInfo[]>("desktop-changed", (event) => {
+    const unlisten = listen<(DesktopInfo | null)[]>("desktop-changed", (event) => {
       const infos = event.payload;
       const info = infos[displayIndex];
       if (!info) return;
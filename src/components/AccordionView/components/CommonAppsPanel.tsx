@@ -1,4 +1,4 @@
-import { useEffect } from "react";
+import { useEffect, useState } from "react";
 import { Box, ButtonBase, Link, Tooltip, Typography } from "@mui/material";
 import { useTheme } from "@mui/material/styles";
 import { invoke } from "@tauri-apps/api/core";
@@ -6,16 +6,85 @@ import { useSettingsStore, useUIStore } from "../../../stores";
 import { MODAL_ID } from "../../Modal/Modal.consts";
 import { BG_OVERLAY } from "../../../theme";
 import { AppIconButton } from "../../shared";
+import { appIconUrl, commonAppKey } from "../../../utils";
+import type { CommonApp, CommonAppHealth } from "../../../types";
 
 export default function CommonAppsPanel() {
   const { tc, ui } = useTheme().custom;
-  const { commonApps, loadCommonApps } = useSettingsStore();
+  const { commonApps, appGroups, loadCommonApps } = useSettingsStore();
   const openModal = useUIStore((s) => s.openModal);
+  const [launchError, setLaunchError] = useState<string | null>(null);
+  const [health, setHealth] = useState<Record<string, CommonAppHealth>>({});
 
   useEffect(() => {
     loadCommonApps();
   }, [loadCommonApps]);
 
+  // Moved apps keep their stored path until the next launch; show icons
+  // from where they are now
+  useEffect(() => {
+    let cancelled = false;
+    invoke<CommonAppHealth[]>("check_common_apps")
+      .then((results) => {
+        if (!cancelled) setHealth(Object.fromEntries(results.map((h) => [h.path, h])));
+      })
+      .catch(() => { });
+    return () => { cancelled = true; };
+  }, [commonApps]);
+
+  useEffect(() => {
+    if (!launchError) return;
+    const timer = setTimeout(() => setLaunchError(null), 6000);
+    return () => clearTimeout(timer);
+  }, [launchError]);
+
+  // Ungrouped apps first, then each group in its saved order
+  const sections = [
+    { id: "", name: null, apps: commonApps.filter((a) => !a.group || !appGroups.some((g) => g.id === a.group)) },
+    ...appGroups.map((g) => ({ id: g.id, name: g.name, apps: commonApps.filter((a) => a.group === g.id) })),
+  ].filter((section) => section.apps.length > 0);
+
+  const renderApp = (app: CommonApp) => {
+    const status = health[app.path]?.status;
+    const tooltip = status === "missing"
+      ? `${app.name} wasn't found`
+      : app.default_action === "activate_existing" ? `Open ${app.name}` : `Open new ${app.name} window`;
+    return (
+      <Tooltip key={app.path} title={tooltip} arrow>
+        <ButtonBase
+          onClick={() => {
+            setLaunchError(null);
+            invoke("launch_common_app", { key: commonAppKey(app) }).catch((err) => setLaunchError(String(err)));
+          }}
+          sx={{
+            px: "8px",
+            py: "4px",
+            flexShrink: 0,
+            display: "flex",
+            alignItems: "center",
+            gap: "4px",
+            fontFamily: "inherit",
+            bgcolor: BG_OVERLAY,
+            "&:hover": { bgcolor: BG_OVERLAY },
+            transition: "background-color 0.15s",
+            opacity: status === "missing" ? 0.5 : 1,
+          }}
+        >
+          <Box
+            component="img"
+            src={appIconUrl(health[app.path]?.current_path ?? app.path, 32)}
+            alt=""
+            onError={(e) => { e.currentTarget.style.display = "none"; }}
+            sx={{ width: 14, height: 14 }}
+          />
+          <Typography variant="subtitle1" sx={{ whiteSpace: "nowrap", lineHeight: 1 }}>
+            {app.short_name || app.name}
+          </Typography>
+        </ButtonBase>
+      </Tooltip>
+    );
+  };
+
   return commonApps.length === 0 ? (
     <Box
       sx={{
@@ -39,51 +108,51 @@ export default function CommonAppsPanel() {
       </Link>
     </Box>
   ) : (
-    <Box
-      sx={{
-        display: "flex",
-        alignItems: "center",
-        width: "100%",
-        gap: "4px",
-      }}
-    >
+    <Box sx={{ display: "flex", flexDirection: "column", width: "100%", gap: "2px" }}>
       <Box
         sx={{
           display: "flex",
-          gap: "6px",
-          overflowX: "auto",
-          flex: 1,
-          "&::-webkit-scrollbar": { display: "none" },
+          alignItems: "center",
+          width: "100%",
+          gap: "4px",
         }}
       >
-        {commonApps.map((app) => (
-          <Tooltip key={app.path} title={`Open new ${app.name} instance`} arrow>
-            <ButtonBase
-              onClick={() => {
-                invoke("open_new_app_instance", { path: app.path, launchArgs: app.launch_args || null }).catch(() => {});
-              }}
-              sx={{
-                px: "8px",
-                py: "4px",
-                flexShrink: 0,
-                display: "flex",
-                alignItems: "center",
-                fontFamily: "inherit",
-                bgcolor: BG_OVERLAY,
-                "&:hover": { bgcolor: BG_OVERLAY },
-                transition: "background-color 0.15s",
-              }}
-            >
-              <Typography variant="subtitle1" sx={{ whiteSpace: "nowrap", lineHeight: 1 }}>
-                {app.short_name || app.name}
-              </Typography>
-            </ButtonBase>
-          </Tooltip>
-        ))}
+        <Box
+          sx={{
+            display: "flex",
+            gap: "12px",
+            overflowX: "auto",
+            flex: 1,
+            "&::-webkit-scrollbar": { display: "none" },
+          }}
+        >
+          {sections.map((section) => (
+            <Box key={section.id} sx={{ display: "flex", alignItems: "center", gap: "6px", flexShrink: 0 }}>
+              {section.name && (
+                <Typography
+                  variant="body2"
+                  sx={{ fontSize: ui.fontSize.xs, color: tc(0.5), whiteSpace: "nowrap" }}
+                >
+                  {section.name}
+                </Typography>
+              )}
+              {section.apps.map(renderApp)}
+            </Box>
+          ))}
+        </Box>
+        <Tooltip title="Configure apps" arrow>
+          <AppIconButton icon="tune" onClick={() => openModal(MODAL_ID.COMMON_APPS)} sx={{ flexShrink: 0, fontSize: 14 }} />
+        </Tooltip>
       </Box>
-      <Tooltip title="Configure apps" arrow>
-        <AppIconButton icon="tune" onClick={() => openModal(MODAL_ID.COMMON_APPS)} sx={{ flexShrink: 0, fontSize: 14 }} />
-      </Tooltip>
+      {launchError && (
+        <Typography
+          variant="body2"
+          title={launchError}
+          sx={{ fontSize: ui.fontSize.xs, color: "error.main", overflow: "hidden", textOverflow: "ellipsis", whiteSpace: "nowrap" }}
+        >
+          {launchError}
+        </Typography>
+      )}
     </Box>
   );
 }
@@ -1,24 +1,46 @@
-import { InputBase } from "@mui/material";
+import { Box, InputBase } from "@mui/material";
+import { useTheme } from "@mui/material/styles";
 import { useTodoStore } from "../../../stores";
+import { AppIconButton } from "../../shared";
 
 interface DesktopNamePanelProps {
   desktopId: number;
 }
 
 export default function DesktopNamePanel({ desktopId }: DesktopNamePanelProps) {
+  const { tc } = useTheme().custom;
   const title = useTodoStore((s) => s.allTitles[s.activeDesktopId] ?? "");
+  const pinned = useTodoStore((s) => s.pinnedTitles[s.activeDesktopId] ?? false);
   const updateTitle = useTodoStore((s) => s.updateTitle);
+  const toggleTitlePinned = useTodoStore((s) => s.toggleTitlePinned);
 
   return (
-    <InputBase
-      placeholder="Name this desktop"
-      value={title}
-      onChange={(e) => updateTitle(e.target.value, desktopId)}
-      sx={{
-        width: "100%",
-        px: "8px",
-        "& input": { p: "3px 0" },
-      }}
-    />
+    <Box sx={{ display: "flex", alignItems: "center", "&:hover .pin-btn": { opacity: 1 } }}>
+      <InputBase
+        placeholder="Name this desktop"
+        value={title}
+        onChange={(e) => updateTitle(e.target.value, desktopId)}
+        sx={{
+          flex: 1,
+          minWidth: 0,
+          px: "8px",
+          "& input": { p: "3px 0" },
+        }}
+      />
+      <AppIconButton
+        icon={pinned ? "pinned" : "pin"}
+        className="pin-btn"
+        onClick={() => toggleTitlePinned(desktopId)}
+        sx={{
+          flexShrink: 0,
+          color: pinned ? tc(0.45) : tc(0.2),
+          fontSize: 12,
+          mr: "4px",
+          opacity: pinned ? 1 : 0,
+          transition: "opacity 0.15s",
+          "&:hover": { color: tc(0.5) },
+        }}
+      />
+    </Box>
   );
 }
@@ -10,7 +10,7 @@ interface DesktopsPanelProps {
 
 export default function DesktopsPanel({ displayIndex }: DesktopsPanelProps) {
   const { tc, ui } = useTheme().custom;
-  const { displayGroups } = useUIStore();
+  const { displayGroups, switcherFocus } = useUIStore();
   const { desktop, switchDesktop } = useDesktopStore();
   const accessibilityGranted = useSettingsStore((s) => s.accessibilityGranted);
   const scrollRef = useRef<HTMLDivElement>(null);
@@ -35,6 +35,27 @@ export default function DesktopsPanel({ displayIndex }: DesktopsPanelProps) {
     return () => { cancelAnimationFrame(id); cancelAnimationFrame(id2); };
   }, [desktop.space_id, desktops]);
 
+  // Keyboard switching: the open_switcher hotkey focuses the active desktop
+  useEffect(() => {
+    if (switcherFocus === 0) return;
+    const id = requestAnimationFrame(() => {
+      const activeEl = scrollRef.current?.querySelector("[data-active='true']") as HTMLElement | null;
+      activeEl?.focus();
+    });
+    return () => cancelAnimationFrame(id);
+  }, [switcherFocus]);
+
+  const handleKeyDown = useCallback((e: React.KeyboardEvent<HTMLDivElement>) => {
+    if (e.key !== "ArrowLeft" && e.key !== "ArrowRight") return;
+    const current = document.activeElement as HTMLElement | null;
+    const next = e.key === "ArrowLeft" ? current?.previousElementSibling : current?.nextElementSibling;
+    if (next instanceof HTMLElement) {
+      e.preventDefault();
+      next.focus();
+      next.scrollIntoView({ inline: "center", block: "nearest", behavior: "smooth" });
+    }
+  }, []);
+
   const handleClick = useCallback((spaceId: number, el: HTMLElement) => {
     if (!accessibilityGranted) return;
     switchDesktop(displayIndex, spaceId);
@@ -71,6 +92,7 @@ export default function DesktopsPanel({ displayIndex }: DesktopsPanelProps) {
       ) : desktops.length > 0 ? (
         <Box
           ref={scrollRef}
+          onKeyDown={handleKeyDown}
           sx={{
             display: "flex",
             gap: "6px",
@@ -109,7 +131,7 @@ export default function DesktopsPanel({ displayIndex }: DesktopsPanelProps) {
                     whiteSpace: "nowrap",
                   }}
                 >
-                  {d.title || `Desktop ${d.position + 1}`}
+                  {d.title || d.name}
                 </Typography>
               </ButtonBase>
             );
@@ -1,8 +1,9 @@
+import { useEffect, useRef } from "react";
 import { Box, InputBase, Typography } from "@mui/material";
 import { useTheme } from "@mui/material/styles";
 import { Reorder } from "framer-motion";
 import TodoItem from "./TodoItem";
-import { useTodoStore, EMPTY_TODOS } from "../../../stores";
+import { useTodoStore, useUIStore, EMPTY_TODOS } from "../../../stores";
 import { AppIconButton } from "../../shared";
 
 interface QueuePanelProps {
@@ -16,9 +17,17 @@ export default function QueuePanel({ desktopId }: QueuePanelProps) {
   const setNewText = useTodoStore((s) => s.setNewText);
   const addTodo = useTodoStore((s) => s.addTodo);
   const toggleDone = useTodoStore((s) => s.toggleDone);
+  const togglePinned = useTodoStore((s) => s.togglePinned);
   const updateText = useTodoStore((s) => s.updateText);
   const deleteTodo = useTodoStore((s) => s.deleteTodo);
   const reorderTodos = useTodoStore((s) => s.reorderTodos);
+  const todoInputFocus = useUIStore((s) => s.todoInputFocus);
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  // Quick-add hotkey
+  useEffect(() => {
+    if (todoInputFocus > 0) inputRef.current?.focus();
+  }, [todoInputFocus]);
 
   const active = todos.filter((t) => !t.done);
 
@@ -33,6 +42,7 @@ export default function QueuePanel({ desktopId }: QueuePanelProps) {
       >
         <InputBase
           placeholder="Add task..."
+          inputRef={inputRef}
           value={newText}
           onChange={(e) => setNewText(e.target.value)}
           onKeyDown={(e) => e.key === "Enter" && addTodo(desktopId)}
@@ -87,6 +97,7 @@ export default function QueuePanel({ desktopId }: QueuePanelProps) {
                 </Typography>
               }
               onToggle={() => toggleDone(item.id, desktopId)}
+              onTogglePin={() => togglePinned(item.id, desktopId)}
               onUpdate={(text) => updateText(item.id, text, desktopId)}
               onDelete={() => deleteTodo(item.id, desktopId)}
             />
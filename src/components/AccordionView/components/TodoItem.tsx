@@ -8,6 +8,7 @@ interface TodoItemProps {
   isDone?: boolean;
   dragHandle?: React.ReactNode;
   onToggle: () => void;
+  onTogglePin: () => void;
   onUpdate: (text: string) => void;
   onDelete: () => void;
 }
@@ -17,6 +18,7 @@ export default function TodoItem({
   isDone,
   dragHandle,
   onToggle,
+  onTogglePin,
   onUpdate,
   onDelete,
 }: TodoItemProps) {
@@ -32,7 +34,7 @@ export default function TodoItem({
         py: `${ui.spacing.itemPy}px`,
         gap: `${ui.spacing.gap}px`,
         transition: "background 0.1s",
-        "&:hover .delete-btn": { opacity: 1 },
+        "&:hover .delete-btn, &:hover .pin-btn": { opacity: 1 },
       }}
     >
       {dragHandle}
@@ -62,6 +64,22 @@ export default function TodoItem({
           "& input::placeholder": { color: tc(0.25) },
         }}
       />
+      <AppIconButton
+        icon={item.pinned ? "pinned" : "pin"}
+        className="pin-btn"
+        onClick={() => onTogglePin()}
+        sx={{
+          flexShrink: 0,
+          color: item.pinned ? tc(0.45) : tc(0.2),
+          fontSize: 12,
+          p: "0 2px",
+          lineHeight: 1,
+          // Pinned items keep the pin visible so the carry-over is obvious
+          opacity: item.pinned ? 1 : 0,
+          transition: "opacity 0.15s",
+          "&:hover": { color: tc(0.5) },
+        }}
+      />
       <AppIconButton
         icon="close"
         className="delete-btn"
@@ -1,26 +1,36 @@
 import { useState, useEffect } from "react";
-import { Box, Tab, Tabs, Typography } from "@mui/material";
+import { Box, Checkbox, Link, Tab, Tabs, Typography } from "@mui/material";
 import { useTheme } from "@mui/material/styles";
 import { invoke } from "@tauri-apps/api/core";
 import { useSettingsStore } from "../../../stores";
-import type { CommonApp } from "../../../types";
+import type { CommonApp, InstalledApp, LaunchReport } from "../../../types";
 import DefaultModal from "./DefaultModal";
 import { BG_OVERLAY_LIGHT } from "../../../theme";
 import { openUrl } from "@tauri-apps/plugin-opener";
 import { AppInput, AppIconButton } from "../../shared";
+import { formatRelativeTime } from "../../../utils";
 
 const LAUNCH_ARGS_TIP = "common_apps_launch_args";
 
+/** The bundle name shown in the launch log, e.g. "Safari" for Safari.app. */
+const appName = (path: string) => path.split("/").pop()?.replace(/\.app$/, "") || path;
+
+/** Same app: by bundle id when both have one, otherwise by path. */
+const sameApp = (a: CommonApp, b: CommonApp) =>
+  a.bundle_id && b.bundle_id ? a.bundle_id.toLowerCase() === b.bundle_id.toLowerCase() : a.path === b.path;
+
 export default function CommonAppsModal() {
   const { tc, ui } = useTheme().custom;
   const { commonApps, setCommonApps, dismissedTips, dismissTip, loadDismissedTips } = useSettingsStore();
-  const [installedApps, setInstalledApps] = useState<CommonApp[]>([]);
+  const [installedApps, setInstalledApps] = useState<InstalledApp[]>([]);
   const [search, setSearch] = useState("");
   const [tab, setTab] = useState(0);
+  const [launchErrors, setLaunchErrors] = useState<Record<string, string>>({});
+  const [launchLog, setLaunchLog] = useState<LaunchReport[]>([]);
 
   useEffect(() => {
     if (installedApps.length === 0) {
-      invoke<CommonApp[]>("list_installed_apps")
+      invoke<InstalledApp[]>("list_installed_apps")
         .then(setInstalledApps)
         .catch(() => { });
     }
@@ -30,11 +40,43 @@ export default function CommonAppsModal() {
     loadDismissedTips();
   }, [loadDismissedTips]);
 
+  // Check custom commands as they're edited
+  useEffect(() => {
+    let cancelled = false;
+    Promise.all(
+      commonApps
+        .filter((a) => a.launch_args)
+        .map((a) =>
+          invoke("validate_launch_command", { command: a.launch_args, rawShell: a.raw_shell ?? false })
+            .then(() => null)
+            .catch((err) => [a.path, String(err)] as const)
+        )
+    ).then((results) => {
+      if (cancelled) return;
+      setLaunchErrors(Object.fromEntries(results.filter((r) => r !== null)));
+    });
+    return () => { cancelled = true; };
+  }, [commonApps]);
+
+  useEffect(() => {
+    if (tab === 2) {
+      invoke<LaunchReport[]>("get_launch_log")
+        .then(setLaunchLog)
+        .catch(() => { });
+    }
+  }, [tab]);
+
+  const clearLaunchLog = () => {
+    invoke("clear_launch_log")
+      .then(() => setLaunchLog([]))
+      .catch(() => { });
+  };
+
   const toggleApp = (app: CommonApp) => {
-    if (commonApps.some((a) => a.path === app.path)) {
-      setCommonApps(commonApps.filter((a) => a.path !== app.path));
+    if (commonApps.some((a) => sameApp(a, app))) {
+      setCommonApps(commonApps.filter((a) => !sameApp(a, app)));
     } else {
-      setCommonApps([...commonApps, app]);
+      setCommonApps([...commonApps, { name: app.name, path: app.path, bundle_id: app.bundle_id }]);
     }
   };
 
@@ -50,7 +92,13 @@ export default function CommonAppsModal() {
     ));
   };
 
-  const isSelected = (app: CommonApp) => commonApps.some((a) => a.path === app.path);
+  const updateRawShell = (path: string, rawShell: boolean) => {
+    setCommonApps(commonApps.map((a) =>
+      a.path === path ? { ...a, raw_shell: rawShell || undefined } : a
+    ));
+  };
+
+  const isSelected = (app: CommonApp) => commonApps.some((a) => sameApp(a, app));
 
   const filtered = installedApps.filter((a) =>
     a.name.toLowerCase().includes(search.toLowerCase())
@@ -67,6 +115,7 @@ export default function CommonAppsModal() {
       >
         <Tab label="Search" sx={{ minWidth: "unset", px: "10px" }} />
         <Tab label="Selected" sx={{ minWidth: "unset", px: "10px" }} />
+        <Tab label="Log" sx={{ minWidth: "unset", px: "10px" }} />
       </Tabs>
 
       {tab === 0 && (
@@ -211,11 +260,102 @@ export default function CommonAppsModal() {
                     p: "2px 0",
                   }}
                 />
+                {app.launch_args && (
+                  <Box
+                    component="label"
+                    sx={{ display: "flex", alignItems: "center", gap: "6px", cursor: "pointer" }}
+                  >
+                    <Checkbox
+                      size="small"
+                      checked={app.raw_shell ?? false}
+                      onChange={(e) => updateRawShell(app.path, e.target.checked)}
+                      sx={{ p: 0 }}
+                    />
+                    <Typography variant="body2" sx={{ fontSize: ui.fontSize.xs }}>Run through shell</Typography>
+                  </Box>
+                )}
+                {launchErrors[app.path] && (
+                  <Typography variant="body2" sx={{ fontSize: ui.fontSize.xs, color: "error.main" }}>
+                    {launchErrors[app.path]}
+                  </Typography>
+                )}
               </Box>
             ))
           )}
         </Box>
       )}
+
+      {tab === 2 && (
+        <Box
+          sx={{
+            flex: 1,
+            overflow: "auto",
+            py: "4px",
+            "&::-webkit-scrollbar": { display: "none" },
+          }}
+        >
+          {launchLog.length === 0 ? (
+            <Typography variant="body2" sx={{ py: "8px" }}>
+              No launches yet.
+            </Typography>
+          ) : (
+            <>
+              <Box sx={{ display: "flex", justifyContent: "flex-end" }}>
+                <Link
+                  component="button"
+                  onClick={clearLaunchLog}
+                  sx={{ fontSize: ui.fontSize.xs, color: tc(0.5) }}
+                >
+                  Clear log
+                </Link>
+              </Box>
+              {launchLog.map((report) => (
+                <Box
+                  key={`${report.started_at}-${report.path}`}
+                  sx={{
+                    display: "flex",
+                    flexDirection: "column",
+                    gap: "2px",
+                    py: "4px",
+                    "&:not(:last-child)": { borderBottom: `1px solid ${BG_OVERLAY_LIGHT}` },
+                  }}
+                >
+                  <Box sx={{ display: "flex", alignItems: "center", gap: "6px" }}>
+                    <Typography
+                      variant="subtitle1"
+                      sx={{ flex: 1, color: report.success ? undefined : "error.main" }}
+                    >
+                      {report.success ? "✓" : "✗"} {appName(report.path)}
+                    </Typography>
+                    <Typography variant="body2" sx={{ fontSize: ui.fontSize.xs, color: tc(0.5) }}>
+                      {formatRelativeTime(new Date(report.started_at))} · {report.duration_ms}ms
+                    </Typography>
+                  </Box>
+                  <Typography
+                    variant="body2"
+                    sx={{ fontSize: ui.fontSize.xs, color: tc(0.6), fontFamily: "monospace", wordBreak: "break-all" }}
+                  >
+                    {report.argv.join(" ")}
+                  </Typography>
+                  {report.fallback_from && (
+                    <Typography variant="body2" sx={{ fontSize: ui.fontSize.xs, color: tc(0.5) }}>
+                      Fell back from {report.fallback_from.strategy.replace(/_/g, " ")}
+                    </Typography>
+                  )}
+                  {!report.success && (
+                    <Typography
+                      variant="body2"
+                      sx={{ fontSize: ui.fontSize.xs, color: "error.main", whiteSpace: "pre-wrap", wordBreak: "break-word" }}
+                    >
+                      {report.stderr || (report.exit_code === null ? "Terminated" : `Exited with code ${report.exit_code}`)}
+                    </Typography>
+                  )}
+                </Box>
+              ))}
+            </>
+          )}
+        </Box>
+      )}
     </DefaultModal>
   );
 }
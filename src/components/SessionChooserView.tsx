@@ -13,7 +13,7 @@ export default function SessionChooserView() {
 
   const { setView } = useUIStore();
   const hasExistingSession = useUIStore((s) => s.hasExistingSession);
-  const { clearAll } = useTodoStore();
+  const { clearSession } = useTodoStore();
   const { setContextHistory } = useDesktopStore();
   const { setAllSpaces } = useSettingsStore();
 
@@ -75,7 +75,7 @@ export default function SessionChooserView() {
             onClick={() => {
               invoke("start_new_session")
                 .then(() => {
-                  clearAll();
+                  clearSession();
                   setView("todos");
                   emit("session-action", { action: "new" });
                 })
@@ -10,6 +10,8 @@ import {
   OpenWith,
   Pause,
   PlayArrow,
+  PushPin,
+  PushPinOutlined,
   Remove,
   Replay,
   Tune,
@@ -24,6 +26,8 @@ const ICONS = {
   openWith: OpenWith,
   pause: Pause,
   play: PlayArrow,
+  pin: PushPinOutlined,
+  pinned: PushPin,
   remove: Remove,
   replay: Replay,
   tune: Tune,
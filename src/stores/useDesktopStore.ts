@@ -18,6 +18,8 @@ export const useDesktopStore = create<DesktopState>((set) => ({
   desktop: {
     space_id: 0,
     position: 0,
+    display_index: 0,
+    local: 1,
     name: "Desktop 1",
     color: "#F5E6A3",
     is_fullscreen: false,
@@ -1,6 +1,6 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
-import type { SpaceInfo, DesktopInfo, CommonApp, Settings } from "../types";
+import type { SpaceInfo, DesktopInfo, CommonApp, AppGroup, Settings } from "../types";
 
 interface SettingsState {
   timerPresets: number[];
@@ -11,6 +11,7 @@ interface SettingsState {
   allSpaces: SpaceInfo[];
   hiddenPanels: string[];
   commonApps: CommonApp[];
+  appGroups: AppGroup[];
   autoHideDelay: number;
   dismissedTips: string[];
 
@@ -42,6 +43,7 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
   allSpaces: [],
   hiddenPanels: [],
   commonApps: [],
+  appGroups: [],
   autoHideDelay: 0,
   dismissedTips: [],
 
@@ -92,6 +94,7 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
           notifyFlash: s.notify_flash ?? true,
           hiddenPanels: s.hidden_panels ?? [],
           autoHideDelay: s.auto_hide_delay ?? 0,
+          appGroups: s.app_groups ?? [],
         });
         // Resize window based on potentially changed hidden panels
         import("./useUIStore").then(({ useUIStore }) => {
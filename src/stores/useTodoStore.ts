@@ -1,7 +1,6 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import type { TodoItem } from "../types";
-import { useHistoryStore } from "./useHistoryStore";
 import { useDesktopStore } from "./useDesktopStore";
 import { useUIStore } from "./useUIStore";
 
@@ -10,6 +9,7 @@ export const EMPTY_TODOS: TodoItem[] = [];
 interface TodoState {
   allTodos: Record<number, TodoItem[]>;
   allTitles: Record<number, string>;
+  pinnedTitles: Record<number, boolean>;
   activeDesktopId: number;
   newText: string;
   saveTimer: ReturnType<typeof setTimeout> | null;
@@ -22,22 +22,26 @@ interface TodoState {
   loadAll: (desktopIds: number[]) => Promise<void>;
   switchTo: (desktopId: number) => void;
   clearAll: () => void;
+  clearSession: () => void;
   reloadDesktop: (desktopId: number) => Promise<void>;
 
   saveTodos: (desktopId: number, items: TodoItem[]) => void;
   saveTitle: (desktopId: number, title: string) => void;
 
   addTodo: (desktopId: number) => void;
-  toggleDone: (id: string, desktopId: number) => void;
+  toggleDone: (id: string, desktopId: number) => Promise<void>;
+  togglePinned: (id: string, desktopId: number) => void;
   updateText: (id: string, text: string, desktopId: number) => void;
   deleteTodo: (id: string, desktopId: number) => void;
   reorderTodos: (reordered: TodoItem[], desktopId: number) => void;
   updateTitle: (value: string, desktopId: number) => void;
+  toggleTitlePinned: (desktopId: number) => void;
 }
 
 export const useTodoStore = create<TodoState>((set, get) => ({
   allTodos: {},
   allTitles: {},
+  pinnedTitles: {},
   activeDesktopId: 0,
   newText: "",
   saveTimer: null,
@@ -56,20 +60,23 @@ export const useTodoStore = create<TodoState>((set, get) => ({
   loadAll: async (desktopIds) => {
     const results = await Promise.all(
       desktopIds.map(async (id) => {
-        const [todos, title] = await Promise.all([
+        const [todos, title, pinned] = await Promise.all([
           invoke<TodoItem[]>("get_todos", { desktop: id }).catch(() => [] as TodoItem[]),
           invoke<string>("get_title", { desktop: id }).catch(() => ""),
+          invoke<boolean>("get_title_pinned", { desktop: id }).catch(() => false),
         ]);
-        return { id, todos, title };
+        return { id, todos, title, pinned };
       })
     );
     const allTodos: Record<number, TodoItem[]> = {};
     const allTitles: Record<number, string> = {};
+    const pinnedTitles: Record<number, boolean> = {};
     for (const r of results) {
       allTodos[r.id] = r.todos;
       allTitles[r.id] = r.title;
+      pinnedTitles[r.id] = r.pinned;
     }
-    set({ allTodos, allTitles });
+    set({ allTodos, allTitles, pinnedTitles });
   },
 
   switchTo: (desktopId) => {
@@ -77,17 +84,34 @@ export const useTodoStore = create<TodoState>((set, get) => ({
   },
 
   clearAll: () => {
-    set({ allTodos: {}, allTitles: {} });
+    set({ allTodos: {}, allTitles: {}, pinnedTitles: {} });
+  },
+
+  // Mirrors start_new_session: pinned todos and titles carry over
+  clearSession: () => {
+    set((state) => ({
+      allTodos: Object.fromEntries(
+        Object.entries(state.allTodos).map(([id, todos]) => [
+          id,
+          todos.filter((t) => t.pinned).map((t) => ({ ...t, done: false })),
+        ])
+      ),
+      allTitles: Object.fromEntries(
+        Object.entries(state.allTitles).filter(([id]) => state.pinnedTitles[Number(id)])
+      ),
+    }));
   },
 
   reloadDesktop: async (desktopId) => {
-    const [todos, title] = await Promise.all([
+    const [todos, title, pinned] = await Promise.all([
       invoke<TodoItem[]>("get_todos", { desktop: desktopId }).catch(() => [] as TodoItem[]),
       invoke<string>("get_title", { desktop: desktopId }).catch(() => ""),
+      invoke<boolean>("get_title_pinned", { desktop: desktopId }).catch(() => false),
     ]);
     set((state) => ({
       allTodos: { ...state.allTodos, [desktopId]: todos },
       allTitles: { ...state.allTitles, [desktopId]: title },
+      pinnedTitles: { ...state.pinnedTitles, [desktopId]: pinned },
     }));
   },
 
@@ -119,20 +143,38 @@ export const useTodoStore = create<TodoState>((set, get) => ({
     set({ saveTimer: newTimer });
   },
 
-  toggleDone: (id, desktopId) => {
-    const { allTodos, saveTodos } = get();
+  toggleDone: async (id, desktopId) => {
+    const { allTodos, saveTimer } = get();
     const current = allTodos[desktopId] ?? [];
-    const item = current.find((t) => t.id === id);
-    if (!item) return;
+    if (!current.some((t) => t.id === id)) return;
 
-    const updated = current.filter((t) => t.id !== id);
+    // Save pending edits first so the backend completes the latest list
+    if (saveTimer) {
+      clearTimeout(saveTimer);
+      set({ saveTimer: null });
+      await invoke("save_todos", { desktop: desktopId, todos: current }).catch(() => {});
+    }
+    const title = get().allTitles[desktopId];
+    // Same name the tray and switcher show: custom name, else "Desktop N"
+    const summary = useUIStore.getState().displayGroups
+      .flatMap((g) => g.desktops)
+      .find((d) => d.space_id === desktopId);
+    const desktopName = title || summary?.name || useDesktopStore.getState().desktop.name;
+    // Recurring todos come back rescheduled; others come back done
+    const updated = await invoke<TodoItem[]>("complete_todo", { desktop: desktopId, id, desktopName }).catch(() => null);
+    if (!updated) return;
+    set((state) => ({
+      allTodos: { ...state.allTodos, [desktopId]: updated },
+    }));
+  },
+
+  togglePinned: (id, desktopId) => {
+    const { allTodos, saveTodos } = get();
+    const current = allTodos[desktopId] ?? [];
+    const updated = current.map((t) => (t.id === id ? { ...t, pinned: !t.pinned } : t));
     set((state) => ({
       allTodos: { ...state.allTodos, [desktopId]: updated },
     }));
-    const title = get().allTitles[desktopId];
-    const position = useDesktopStore.getState().desktop.position;
-    const desktopName = title || `Desktop ${position + 1}`;
-    useHistoryStore.getState().addCompleted(item.text, desktopId, desktopName);
 
     const timer = get().saveTimer;
     if (timer) clearTimeout(timer);
@@ -213,4 +255,12 @@ export const useTodoStore = create<TodoState>((set, get) => ({
     const newTimer = setTimeout(() => saveTitle(desktopId, value), 300);
     set({ titleTimer: newTimer });
   },
+
+  toggleTitlePinned: (desktopId) => {
+    const pinned = !get().pinnedTitles[desktopId];
+    set((state) => ({
+      pinnedTitles: { ...state.pinnedTitles, [desktopId]: pinned },
+    }));
+    invoke("save_title_pinned", { desktop: desktopId, pinned }).catch(() => {});
+  },
 }));
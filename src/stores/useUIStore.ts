@@ -25,6 +25,10 @@ interface UIState {
   hasExistingSession: boolean;
   autoHideCountdown: number | null;
   autoHidePaused: boolean;
+  /** Bumped to ask the task input to take focus. */
+  todoInputFocus: number;
+  /** Bumped to ask the desktops panel to focus the active desktop. */
+  switcherFocus: number;
 
   setView: (v: ViewType) => void;
   setCollapsed: (c: boolean) => void;
@@ -40,6 +44,8 @@ interface UIState {
   dismissUpdate: () => void;
   setAutoHideCountdown: (v: number | null) => void;
   setAutoHidePaused: (v: boolean) => void;
+  focusTodoInput: () => void;
+  focusSwitcher: () => void;
 
   checkPosition: () => Promise<void>;
   snapToMonitor: (overrideAnchor?: AnchorPosition) => Promise<void>;
@@ -66,6 +72,8 @@ export const useUIStore = create<UIState>((set, get) => ({
   hasExistingSession: false,
   autoHideCountdown: null,
   autoHidePaused: false,
+  todoInputFocus: 0,
+  switcherFocus: 0,
 
   setView: (v) => set({ view: v }),
   setCollapsed: (c) => set({ collapsed: c }),
@@ -81,6 +89,8 @@ export const useUIStore = create<UIState>((set, get) => ({
   dismissUpdate: () => set({ updateAvailable: null, updateStatus: "idle" }),
   setAutoHideCountdown: (v) => set({ autoHideCountdown: v }),
   setAutoHidePaused: (v) => set({ autoHidePaused: v }),
+  focusTodoInput: () => set((s) => ({ todoInputFocus: s.todoInputFocus + 1 })),
+  focusSwitcher: () => set((s) => ({ switcherFocus: s.switcherFocus + 1 })),
 
   checkPosition: async () => {
     const m = get().monitorRef;
@@ -6,25 +6,45 @@ export type AnchorPosition =
 export interface DesktopInfo {
   space_id: number;
   position: number;
+  display_index: number;
+  local: number;
   name: string;
+  custom_name?: string;
   color: string;
   is_fullscreen: boolean;
+  owner?: SpaceOwner;
+  parent_id?: number;
+}
+
+export interface SpaceOwner {
+  name: string;
+  bundle_id?: string;
 }
 
 export interface TodoItem {
   id: string;
   text: string;
   done: boolean;
+  pinned?: boolean;
+  recurrence?: string;
+  due?: string;
+  /** Local wall-clock time the series started at, e.g. "2026-03-07T02:30:00". */
+  dtstart?: string;
 }
 
 export interface DesktopSummary {
   space_id: number;
   position: number;
+  display_index: number;
+  local: number;
   name: string;
+  custom_name?: string;
   title: string;
   color: string;
   todo_count: number;
   is_fullscreen: boolean;
+  owner?: SpaceOwner;
+  parent_id?: number;
 }
 
 export interface SpaceInfo {
@@ -45,6 +65,7 @@ export type ContextHistory = Record<number, SavedContext[]>;
 
 export interface Settings {
   custom_colors: Record<number, string>;
+  custom_names: Record<number, string>;
   setup_complete: boolean;
   desktop_count: number;
   timer_presets: number[];
@@ -52,6 +73,68 @@ export interface Settings {
   notify_flash: boolean;
   hidden_panels: string[];
   auto_hide_delay: number;
+  rollover_time?: string;
+  rollover_carry: CarryMode;
+  completed_retention_days?: number;
+  exclude_fullscreen: boolean;
+  fullscreen_todos_to_parent: boolean;
+  hotkeys: Partial<Record<HotkeyAction, string>>;
+  launch_apps_on_restore: boolean;
+  /** Bookmarks shown on every desktop; per-desktop ones come from get_bookmarks. */
+  bookmarks: Bookmark[];
+  app_groups: AppGroup[];
+}
+
+export type HotkeyAction =
+  | "toggle_windows"
+  | "quick_add_todo"
+  | "start_timer"
+  | "previous_desktop"
+  | "open_switcher";
+
+export interface HotkeyError {
+  action: HotkeyAction;
+  accelerator: string;
+  message: string;
+}
+
+export interface HotkeyReport {
+  saved: boolean;
+  errors: HotkeyError[];
+}
+
+export type CarryMode = "all" | "pinned" | "none";
+
+export interface RolloverSummary {
+  archived_desktops: number;
+  done_count: number;
+  open_count: number;
+  carried_count: number;
+  rolled_at: string;
+}
+
+export interface OrphanedDesktop {
+  desktop_id: number;
+  custom_name?: string;
+  title: string;
+  todo_count: number;
+  history_count: number;
+  has_color: boolean;
+  last_display?: number;
+  last_local?: number;
+}
+
+export interface SpacePosition {
+  desktop_id: number;
+  display: number;
+  position: number;
+  local: number;
+}
+
+export interface SpaceMove {
+  desktop_id: number;
+  old: SpacePosition;
+  new: SpacePosition;
 }
 
 export interface DisplayGroup {
@@ -61,9 +144,72 @@ export interface DisplayGroup {
 
 export interface CommonApp {
   name: string;
+  /** Last known location; launches resolve bundle_id first. */
   path: string;
+  bundle_id?: string | null;
   short_name?: string;
+  /** Argv template with {path}, {app_name} and {desktop_title} placeholders. */
   launch_args?: string;
+  /** Run launch_args through `sh -c` instead of as an argv template. */
+  raw_shell?: boolean;
+  /** Position among the common apps; matches the list order. */
+  order?: number;
+  /** AppGroup id, or null for ungrouped. */
+  group?: string | null;
+  default_action?: AppAction;
+}
+
+export type AppAction = "activate_existing" | "new_window" | "new_window_on_monitor";
+
+export interface AppGroup {
+  /** Empty for a group that hasn't been saved yet. */
+  id: string;
+  name: string;
+}
+
+export interface InstalledApp {
+  name: string;
+  path: string;
+  bundle_id: string | null;
+  version: string | null;
+}
+
+export type AppHealthStatus = "ok" | "moved" | "missing";
+
+export interface CommonAppHealth {
+  name: string;
+  path: string;
+  bundle_id: string | null;
+  status: AppHealthStatus;
+  current_path: string | null;
+}
+
+export type LaunchStrategy =
+  | { strategy: "open_new" }
+  | { strategy: "command"; command: string; raw_shell: boolean }
+  | { strategy: "unsupported" };
+
+export type LaunchRecipe = LaunchStrategy & {
+  bundle_id: string;
+  name: string;
+  note: string | null;
+};
+
+export interface RecipeRegistry {
+  version: number;
+  overrides_path: string;
+  overrides_error: string | null;
+  recipes: LaunchRecipe[];
+}
+
+export type BookmarkKind = "url" | "file" | "folder" | "app";
+
+export interface Bookmark {
+  id: string;
+  kind: BookmarkKind;
+  label: string;
+  /** A URL for "url", otherwise an absolute path. */
+  value: string;
 }
 
 export interface CompletedItem {
@@ -74,6 +220,79 @@ export interface CompletedItem {
   completed_at: string;
 }
 
+export interface CompletedQuery {
+  from?: string;
+  to?: string;
+  desktop_id?: number;
+  offset?: number;
+  limit?: number;
+}
+
+export interface CompletedPage {
+  items: CompletedItem[];
+  total: number;
+}
+
+export type SwitchResult =
+  | "switched"
+  | "already_there"
+  | "target_not_on_display"
+  | "permission_denied"
+  | "no_plan"
+  | "timed_out";
+
+export interface DesktopMatch {
+  space_id: number;
+  display_index: number;
+  name: string;
+  title: string;
+  matched: string;
+  score: number;
+}
+
+export interface QuerySwitch {
+  result: SwitchResult | null;
+  candidates: DesktopMatch[];
+}
+
+/** Per-display back/forward history of desktop ids (indexed by display). */
+export interface NavHistory {
+  entries: number[];
+  cursor: number;
+}
+
+export type LaunchStrategyUsed = "open" | "open_new" | "command" | "shell" | "new_window_script";
+
+export interface LaunchReport {
+  path: string;
+  strategy: LaunchStrategyUsed;
+  argv: string[];
+  success: boolean;
+  /** Null when the process couldn't be spawned or was killed by a signal. */
+  exit_code: number | null;
+  stderr: string;
+  duration_ms: number;
+  started_at: string;
+  /** The attempt that failed before this strategy was tried. */
+  fallback_from: LaunchReport | null;
+}
+
+export interface AppLaunchResult {
+  name: string;
+  path: string;
+  ok: boolean;
+  error: string | null;
+  report: LaunchReport;
+}
+
+export interface AppUsage {
+  bundle_id: string;
+  name: string;
+  seconds: number;
+  activations: number;
+  last_used: string;
+}
+
 export type ViewType =
   | "loading"
   | "setup"
@@ -1,5 +1,5 @@
 import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
-import type { AnchorPosition } from "./types";
+import type { AnchorPosition, CommonApp } from "./types";
 
 export const currentWindow = getCurrentWebviewWindow();
 
@@ -57,3 +57,13 @@ export function formatCountdown(total: number): string {
   const s = total % 60;
   return `${String(h).padStart(2, "0")}:${String(m).padStart(2, "0")}:${String(s).padStart(2, "0")}`;
 }
+
+/** URL of an app bundle's icon, rendered as a PNG by the `appicon` protocol. */
+export function appIconUrl(path: string, size = 32): string {
+  return `appicon://localhost/${size}?path=${encodeURIComponent(path)}`;
+}
+
+/** Identifier used by reorder_common_apps and launch_common_app. */
+export function commonAppKey(app: CommonApp): string {
+  return app.bundle_id ? app.bundle_id.toLowerCase() : app.path;
+}